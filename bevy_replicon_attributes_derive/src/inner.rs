@@ -4,24 +4,261 @@
 
 //standard shortcuts
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, parse_quote, DeriveInput};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Fields, Ident, Type};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Parses `#[visibility_attribute(packed)]` from the container attributes.
+fn parse_packed(attrs: &[Attribute]) -> syn::Result<bool>
+{
+    let mut packed = false;
+
+    for attr in attrs.iter().filter(|a| a.path().is_ident("visibility_attribute"))
+    {
+        attr.parse_nested_meta(
+            |meta|
+            {
+                if meta.path.is_ident("packed")
+                {
+                    packed = true;
+                    return Ok(());
+                }
+                Err(meta.error("unsupported visibility_attribute option, expected `packed`"))
+            }
+        )?;
+    }
+
+    Ok(packed)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the number of bits a packed field occupies.
+fn packed_bits(ty: &Type) -> syn::Result<u32>
+{
+    let Type::Path(path) = ty else { return Err(syn::Error::new_spanned(ty, "packed fields must be integers or bools")); };
+
+    let bits = match path.path.get_ident().map(|i| i.to_string()).as_deref()
+    {
+        Some("bool")              => 1,
+        Some("u8")  | Some("i8")  => 8,
+        Some("u16") | Some("i16") => 16,
+        Some("u32") | Some("i32") => 32,
+        Some("u64") | Some("i64") => 64,
+        _ => return Err(syn::Error::new_spanned(ty, "packed fields must be fixed-size integers or bools")),
+    };
+
+    Ok(bits)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Converts a packed field to `u64` without sign extension.
+fn packed_field(ty: &Type, value: &TokenStream2) -> syn::Result<TokenStream2>
+{
+    let unsigned = match packed_bits(ty)?
+    {
+        1  => return Ok(quote!{ (*#value as u64) }),
+        8  => quote!{ u8 },
+        16 => quote!{ u16 },
+        32 => quote!{ u32 },
+        _  => quote!{ u64 },
+    };
+
+    Ok(quote!{ (*#value as #unsigned as u64) })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes an expression that packs fields into the low bits of a `u64`.
+///
+/// The first field occupies the highest bits.
+fn packed_fields(fields: &[(Type, TokenStream2)], available_bits: u32, span: &Ident) -> syn::Result<TokenStream2>
+{
+    let total_bits = fields.iter().map(|(ty, _)| packed_bits(ty)).sum::<syn::Result<u32>>()?;
+    if total_bits > available_bits
+    {
+        return Err(syn::Error::new_spanned(
+            span,
+            format!("packed fields require {total_bits} bits but only {available_bits} bits are available")
+        ));
+    }
+
+    let mut shift = total_bits;
+    let mut parts = Vec::with_capacity(fields.len());
+    for (ty, value) in fields.iter()
+    {
+        shift -= packed_bits(ty)?;
+        let field = packed_field(ty, value)?;
+        parts.push(match shift { 0 => quote!{ #field }, _ => quote!{ (#field << #shift) } });
+    }
+
+    match parts.is_empty()
+    {
+        true  => Ok(quote!{ 0u64 }),
+        false => Ok(quote!{ 0u64 #(| #parts)* }),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes an expression that hashes a sequence of field references.
+fn hashed_fields(prefix: Option<usize>, fields: &[(Type, TokenStream2)]) -> TokenStream2
+{
+    let values = fields.iter().map(|(_, value)| value);
+    match prefix
+    {
+        Some(prefix) => quote!{ hash_visibility_attribute(&(#prefix as u64, #(#values,)*)) },
+        None         => quote!{ hash_visibility_attribute(&(#(#values,)*)) },
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes a destructuring pattern for a set of fields, returning the pattern and the bound field references.
+fn destructure(fields: &Fields) -> (TokenStream2, Vec<(Type, TokenStream2)>)
+{
+    match fields
+    {
+        Fields::Unit => (quote!{}, Vec::default()),
+        Fields::Named(named) =>
+        {
+            let names: Vec<&Ident> = named.named.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+            let bound = named.named.iter().map(|f| { let name = f.ident.as_ref().unwrap(); (f.ty.clone(), quote!{ #name }) }).collect();
+            (quote!{ { #(#names,)* } }, bound)
+        }
+        Fields::Unnamed(unnamed) =>
+        {
+            let names: Vec<Ident> = (0..unnamed.unnamed.len()).map(|i| format_ident!("__field{}", i)).collect();
+            let bound = unnamed.unnamed.iter().zip(names.iter()).map(|(f, name)| (f.ty.clone(), quote!{ #name })).collect();
+            (quote!{ ( #(#names,)* ) }, bound)
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Number of bits needed to record a variant index.
+fn tag_bits(num_variants: usize) -> u32
+{
+    match num_variants
+    {
+        0 | 1 => 0,
+        n     => usize::BITS - (n - 1).leading_zeros(),
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
 pub(crate) fn derive_visibility_attribute_impl(input: TokenStream) -> TokenStream
 {
     let mut ast = parse_macro_input!(input as DeriveInput);
-    ast.generics
-        .make_where_clause()
-        .predicates
-        .push(parse_quote! { Self: Default + PartialEq });
+    let packed = match parse_packed(&ast.attrs)
+    {
+        Ok(packed) => packed,
+        Err(err)   => return err.to_compile_error().into(),
+    };
+    let struct_name = ast.ident.clone();
+
+    // Zero-sized structs use the default attribute id.
+    if let Data::Struct(data) = &ast.data
+    {
+        if data.fields.is_empty() && !packed
+        {
+            ast.generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote! { Self: Default + PartialEq });
+            let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+            return TokenStream::from(quote! {
+                impl #impl_generics DefaultVisibilityAttribute for #struct_name #ty_generics #where_clause
+                {}
+            });
+        }
+    }
+
+    // Build the inner id expression.
+    let mut hashed_types = Vec::default();
+    let inner_id = match &ast.data
+    {
+        Data::Struct(data) =>
+        {
+            let (pattern, fields) = destructure(&data.fields);
+            let id = match packed
+            {
+                true  => packed_fields(&fields, 64, &struct_name),
+                false => { hashed_types.extend(fields.iter().map(|(ty, _)| ty.clone())); Ok(hashed_fields(None, &fields)) }
+            };
+            id.map(|id| quote!{ let Self #pattern = self; #id })
+        }
+        Data::Enum(data) =>
+        {
+            let num_variants = data.variants.len();
+            let tag_bits = tag_bits(num_variants);
+            let arms = data.variants
+                .iter()
+                .enumerate()
+                .map(
+                    |(idx, variant)|
+                    {
+                        let variant_name = &variant.ident;
+                        let (pattern, fields) = destructure(&variant.fields);
+                        let id = match packed
+                        {
+                            true =>
+                            {
+                                let payload = packed_fields(&fields, 64 - tag_bits, variant_name)?;
+                                match tag_bits
+                                {
+                                    0 => payload,
+                                    _ => { let shift = 64 - tag_bits; quote!{ ((#idx as u64) << #shift) | #payload } }
+                                }
+                            }
+                            // Unit variants are hashed like other variants so their ids can't collide.
+                            false =>
+                            {
+                                hashed_types.extend(fields.iter().map(|(ty, _)| ty.clone()));
+                                hashed_fields(Some(idx), &fields)
+                            }
+                        };
+                        Ok(quote!{ Self::#variant_name #pattern => { #id } })
+                    }
+                )
+                .collect::<syn::Result<Vec<_>>>();
+            arms.map(|arms| quote!{ match self { #(#arms)* } })
+        }
+        Data::Union(_) => Err(syn::Error::new_spanned(&struct_name, "VisibilityAttribute cannot be derived for unions")),
+    };
+    let inner_id = match inner_id
+    {
+        Ok(inner_id) => inner_id,
+        Err(err)     => return err.to_compile_error().into(),
+    };
+
+    // Hashed fields must implement `Hash`.
+    // - We only need explicit bounds when fields may depend on generic parameters.
+    if !ast.generics.params.is_empty()
+    {
+        let where_clause = ast.generics.make_where_clause();
+        for ty in hashed_types
+        {
+            where_clause.predicates.push(parse_quote! { #ty: ::std::hash::Hash });
+        }
+    }
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
-    let struct_name = &ast.ident;
 
     TokenStream::from(quote! {
-        impl #impl_generics DefaultVisibilityAttribute for #struct_name #ty_generics #where_clause
-        {}
+        impl #impl_generics VisibilityAttribute for #struct_name #ty_generics #where_clause
+        {
+            #[allow(unused_variables, clippy::unnecessary_cast)]
+            fn inner_attribute_id(&self) -> u64
+            {
+                #inner_id
+            }
+        }
     })
 }
 
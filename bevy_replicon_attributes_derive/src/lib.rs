@@ -6,7 +6,7 @@ use proc_macro::TokenStream;
 
 //-------------------------------------------------------------------------------------------------------------------
 
-#[proc_macro_derive(VisibilityAttribute)]
+#[proc_macro_derive(VisibilityAttribute, attributes(visibility_attribute))]
 pub fn derive_visibility_attribute(input: TokenStream) -> TokenStream
 {
     inner::derive_visibility_attribute_impl(input)
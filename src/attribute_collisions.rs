@@ -0,0 +1,187 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use siphasher::sip128::{Hasher128, SipHasher13};
+
+//standard shortcuts
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Set when at least one attribute type is registered for collision detection.
+static COLLISION_CHECKS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// [ attribute type id : collision tracker ]
+///
+/// The registry is only written when types are registered. Each type has its own tracker, so recording attributes
+/// only contends with other attributes of the same type.
+static COLLISION_REGISTRY: RwLock<Option<HashMap<TypeId, Arc<Mutex<CollisionTracker>>>>> = RwLock::new(None);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks inner ids of an attribute type.
+struct CollisionTracker
+{
+    type_name: &'static str,
+    response: AttributeCollisionResponse,
+    /// Type-erased formatter for the attribute's `Debug` or `Hash` representation.
+    formatter: fn(&dyn Any) -> String,
+    /// [ inner id : first attribute seen with the id ]
+    seen: HashMap<u64, String>,
+    /// Number of collisions detected.
+    collisions: usize,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn format_attribute<T: Debug + 'static>(attribute: &dyn Any) -> String
+{
+    let Some(attribute) = attribute.downcast_ref::<T>() else { return String::from("<unknown>"); };
+    format!("{attribute:?}")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Uses a 128-bit hash so collisions between representations are negligible compared to 64-bit inner ids.
+fn hash_attribute<T: Hash + 'static>(attribute: &dyn Any) -> String
+{
+    let Some(attribute) = attribute.downcast_ref::<T>() else { return String::from("<unknown>"); };
+    let mut hasher = SipHasher13::new();
+    attribute.hash(&mut hasher);
+    format!("<hash {:032x}>", hasher.finish128().as_u128())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_tracker(type_id: TypeId) -> Option<Arc<Mutex<CollisionTracker>>>
+{
+    let registry = COLLISION_REGISTRY.read().unwrap_or_else(|e| e.into_inner());
+    registry.as_ref().and_then(|r| r.get(&type_id)).cloned()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn register_tracker<T: VisibilityAttribute>(response: AttributeCollisionResponse, formatter: fn(&dyn Any) -> String)
+{
+    if !cfg!(debug_assertions) { return; }
+
+    let tracker = CollisionTracker{
+        type_name: std::any::type_name::<T>(),
+        response,
+        formatter,
+        seen: HashMap::default(),
+        collisions: 0,
+    };
+    let mut registry = COLLISION_REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+    registry
+        .get_or_insert_with(HashMap::default)
+        .insert(TypeId::of::<T>(), Arc::new(Mutex::new(tracker)));
+    COLLISION_CHECKS_ENABLED.store(true, Ordering::Relaxed);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Records an attribute that is being added to a client or condition.
+///
+/// Does nothing unless the attribute type was registered with [`detect_attribute_collisions`] or
+/// [`detect_attribute_collisions_by_hash`].
+pub(crate) fn record_attribute<T: VisibilityAttribute>(attribute: &T, attribute_id: VisibilityAttributeId)
+{
+    if !cfg!(debug_assertions) || !COLLISION_CHECKS_ENABLED.load(Ordering::Relaxed) { return; }
+
+    let Some(tracker) = get_tracker(TypeId::of::<T>()) else { return; };
+    let mut tracker = tracker.lock().unwrap_or_else(|e| e.into_inner());
+
+    let repr = (tracker.formatter)(attribute);
+    let inner_id = attribute_id.inner_id();
+    let Some(existing) = tracker.seen.get(&inner_id)
+    else { tracker.seen.insert(inner_id, repr); return; };
+    if *existing == repr { return; }
+
+    let type_name = tracker.type_name;
+    let existing = existing.clone();
+    let response = tracker.response;
+    tracker.collisions += 1;
+    drop(tracker);
+
+    match response
+    {
+        AttributeCollisionResponse::Log =>
+        {
+            tracing::error!(type_name, inner_id, ?existing, ?repr, "visibility attribute inner id collision");
+        }
+        AttributeCollisionResponse::Panic =>
+        {
+            panic!("visibility attribute inner id collision for {type_name}: {existing} and {repr} both have inner id \
+                {inner_id}");
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Configures how [`detect_attribute_collisions`] reports collisions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AttributeCollisionResponse
+{
+    /// Logs an error.
+    Log,
+    /// Panics.
+    Panic,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Enables inner id collision detection for an attribute type using its `Debug` representation.
+///
+/// When an attribute of this type is added to a client with [`ClientAttributes`] or to a condition with
+/// [`VisibilityCondition::new`], its `Debug` representation is recorded for its inner id. If a later attribute with
+/// the same inner id has a different `Debug` representation, then the collision is reported.
+///
+/// This is useful for validating manual implementations of [`VisibilityAttribute::inner_attribute_id`].
+///
+/// Collision detection is only active in builds with `debug_assertions` enabled. The registry is global and
+/// recorded attributes are only cleared when the type is registered again, so this should only be used during
+/// development.
+pub fn detect_attribute_collisions<T: VisibilityAttribute + Debug>(response: AttributeCollisionResponse)
+{
+    register_tracker::<T>(response, format_attribute::<T>);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Enables inner id collision detection for an attribute type using its `Hash` representation.
+///
+/// This is the same as [`detect_attribute_collisions`], except attributes are distinguished by a 128-bit hash instead
+/// of their `Debug` representation. Use this for types that don't implement `Debug`, or whose `Debug` output doesn't
+/// include all of their fields.
+pub fn detect_attribute_collisions_by_hash<T: VisibilityAttribute + Hash>(response: AttributeCollisionResponse)
+{
+    register_tracker::<T>(response, hash_attribute::<T>);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the number of inner id collisions detected for an attribute type since it was registered for collision
+/// detection.
+///
+/// Returns `0` if the type is not registered or if `debug_assertions` are disabled.
+pub fn attribute_collision_count<T: VisibilityAttribute>() -> usize
+{
+    let Some(tracker) = get_tracker(TypeId::of::<T>()) else { return 0; };
+    let tracker = tracker.lock().unwrap_or_else(|e| e.into_inner());
+    tracker.collisions
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,174 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+
+//standard shortcuts
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Identifies a source that grants attributes to clients.
+///
+/// See [`ClientAttributes::grant`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum AttributeSource
+{
+    /// The source is an entity, such as an item or a buff.
+    Entity(Entity),
+    /// The source is an arbitrary id.
+    Id(u64),
+    /// The source is a name, such as the name of a game system.
+    Name(Cow<'static, str>),
+}
+
+impl From<Entity> for AttributeSource
+{
+    fn from(entity: Entity) -> Self
+    {
+        Self::Entity(entity)
+    }
+}
+
+impl From<u64> for AttributeSource
+{
+    fn from(id: u64) -> Self
+    {
+        Self::Id(id)
+    }
+}
+
+impl From<&'static str> for AttributeSource
+{
+    fn from(name: &'static str) -> Self
+    {
+        Self::Name(Cow::Borrowed(name))
+    }
+}
+
+impl From<String> for AttributeSource
+{
+    fn from(name: String) -> Self
+    {
+        Self::Name(Cow::Owned(name))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks the sources of granted client attributes.
+#[derive(Default)]
+pub(crate) struct AttributeGrants
+{
+    /// [ client : [ attribute : [ source ] ] ]
+    clients: HashMap<u64, HashMap<VisibilityAttributeId, HashSet<AttributeSource>>>,
+    /// [ source : [ (client, attribute) ] ]
+    sources: HashMap<AttributeSource, HashSet<(u64, VisibilityAttributeId)>>,
+    /// Granted client attributes that were also added directly.
+    ///
+    /// Direct adds count as an implicit source, so these attributes are kept when their last grant is revoked.
+    direct: HashSet<(u64, VisibilityAttributeId)>,
+}
+
+impl AttributeGrants
+{
+    /// Records a grant.
+    pub(crate) fn grant(&mut self, client_id: u64, attribute: VisibilityAttributeId, source: AttributeSource)
+    {
+        self.sources.entry(source.clone()).or_default().insert((client_id, attribute));
+        self.clients
+            .entry(client_id)
+            .or_default()
+            .entry(attribute)
+            .or_default()
+            .insert(source);
+    }
+
+    /// Revokes a grant.
+    ///
+    /// Returns `true` if the source was the attribute's last source for the client, including direct adds.
+    pub(crate) fn revoke(&mut self, client_id: u64, attribute: VisibilityAttributeId, source: &AttributeSource) -> bool
+    {
+        let Some(grants) = self.sources.get_mut(source) else { return false; };
+        if !grants.remove(&(client_id, attribute)) { return false; }
+        if grants.is_empty() { self.sources.remove(source); }
+
+        self.remove_client_grant(client_id, attribute, source)
+    }
+
+    /// Revokes all grants from a source.
+    ///
+    /// Returns the client attributes that have no remaining sources, including direct adds.
+    pub(crate) fn revoke_all_from(&mut self, source: &AttributeSource) -> Vec<(u64, VisibilityAttributeId)>
+    {
+        let Some(grants) = self.sources.remove(source) else { return Vec::default(); };
+        grants
+            .into_iter()
+            .filter(|(client_id, attribute)| self.remove_client_grant(*client_id, *attribute, source))
+            .collect()
+    }
+
+    /// Returns `true` if a client attribute has at least one source.
+    pub(crate) fn is_granted(&self, client_id: u64, attribute: VisibilityAttributeId) -> bool
+    {
+        self.clients.get(&client_id).is_some_and(|attributes| attributes.contains_key(&attribute))
+    }
+
+    /// Records whether a granted client attribute was also added directly.
+    ///
+    /// Does nothing if the attribute is not granted.
+    pub(crate) fn set_direct(&mut self, client_id: u64, attribute: VisibilityAttributeId, direct: bool)
+    {
+        match direct
+        {
+            true if self.is_granted(client_id, attribute) => { self.direct.insert((client_id, attribute)); }
+            true => (),
+            false => { self.direct.remove(&(client_id, attribute)); }
+        }
+    }
+
+    /// Forgets which of a client's granted attributes were also added directly.
+    pub(crate) fn clear_direct(&mut self, client_id: u64)
+    {
+        if self.direct.is_empty() { return; }
+        self.direct.retain(|(id, _)| *id != client_id);
+    }
+
+    /// Iterates the sources of a client attribute.
+    pub(crate) fn iter_sources(&self, client_id: u64, attribute: VisibilityAttributeId) -> impl Iterator<Item = &AttributeSource> + '_
+    {
+        self.clients
+            .get(&client_id)
+            .and_then(|attributes| attributes.get(&attribute))
+            .into_iter()
+            .flatten()
+    }
+
+    /// Iterates the attributes granted to a client.
+    pub(crate) fn iter_attributes(&self, client_id: u64) -> impl Iterator<Item = VisibilityAttributeId> + '_
+    {
+        self.clients
+            .get(&client_id)
+            .into_iter()
+            .flat_map(|attributes| attributes.keys().copied())
+    }
+
+    /// Returns `true` if the client attribute has no remaining sources, including direct adds.
+    fn remove_client_grant(&mut self, client_id: u64, attribute: VisibilityAttributeId, source: &AttributeSource) -> bool
+    {
+        let Some(attributes) = self.clients.get_mut(&client_id) else { return false; };
+        let Some(sources) = attributes.get_mut(&attribute) else { return false; };
+        sources.remove(source);
+        if !sources.is_empty() { return false; }
+
+        attributes.remove(&attribute);
+        if attributes.is_empty() { self.clients.remove(&client_id); }
+        !self.direct.remove(&(client_id, attribute))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
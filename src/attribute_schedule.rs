@@ -0,0 +1,168 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_replicon::prelude::ClientVisibility;
+use bevy_replicon::server::server_tick::ServerTick;
+use bevy_replicon::shared::backend::connected_client::NetworkIdMap;
+use bevy_replicon::shared::replicon_tick::RepliconTick;
+
+//standard shortcuts
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum ScheduledChange
+{
+    Add,
+    Remove,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum ScheduledAt
+{
+    /// Elapsed time of the `Time` resource.
+    Elapsed(Duration),
+    /// Duration after the `Time` resource's elapsed time when this is first checked.
+    ///
+    /// Resolved to [`Self::Elapsed`] by [`Self::resolve`].
+    After(Duration),
+    /// Server tick.
+    Tick(RepliconTick),
+    /// Number of ticks after the server tick when this is first checked.
+    ///
+    /// Resolved to [`Self::Tick`] by [`Self::resolve`].
+    AfterTicks(u32),
+}
+
+impl ScheduledAt
+{
+    pub(crate) fn is_timed(&self) -> bool
+    {
+        matches!(self, Self::Elapsed(_) | Self::After(_))
+    }
+
+    /// Converts relative times to absolute times.
+    pub(crate) fn resolve(&mut self, elapsed: Duration, tick: RepliconTick)
+    {
+        match *self
+        {
+            Self::After(duration)   => { *self = Self::Elapsed(elapsed + duration); }
+            Self::AfterTicks(ticks) => { *self = Self::Tick(tick + ticks); }
+            Self::Elapsed(_) | Self::Tick(_) => (),
+        }
+    }
+
+    pub(crate) fn is_due(&self, elapsed: Duration, tick: RepliconTick) -> bool
+    {
+        match self
+        {
+            Self::Elapsed(at)   => *at <= elapsed,
+            Self::Tick(at)      => *at <= tick,
+            Self::After(_)      => false,
+            Self::AfterTicks(_) => false,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub(crate) struct ScheduledAttribute
+{
+    pub(crate) client_id: u64,
+    pub(crate) attribute: VisibilityAttributeId,
+    pub(crate) change: ScheduledChange,
+    pub(crate) at: ScheduledAt,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Pending client attribute changes.
+///
+/// Changes that become due at the same time are applied in the order they were scheduled.
+#[derive(Default)]
+pub(crate) struct AttributeSchedule
+{
+    pending: Vec<ScheduledAttribute>,
+}
+
+impl AttributeSchedule
+{
+    /// Schedules a change.
+    pub(crate) fn schedule(&mut self, scheduled: ScheduledAttribute)
+    {
+        self.pending.push(scheduled);
+    }
+
+    /// Cancels pending timed removals of a client attribute.
+    pub(crate) fn cancel_timed_removals(&mut self, client_id: u64, attribute: VisibilityAttributeId)
+    {
+        self.pending.retain(
+            |s| !(
+                s.client_id == client_id
+                && s.attribute == attribute
+                && s.change == ScheduledChange::Remove
+                && s.at.is_timed()
+            )
+        );
+    }
+
+    /// Drops all pending changes of a client.
+    pub(crate) fn remove_client(&mut self, client_id: u64)
+    {
+        self.pending.retain(|s| s.client_id != client_id);
+    }
+
+    /// Takes all changes that are due, in the order they were scheduled.
+    pub(crate) fn take_due(&mut self, elapsed: Duration, tick: RepliconTick) -> Vec<ScheduledAttribute>
+    {
+        for scheduled in self.pending.iter_mut() { scheduled.at.resolve(elapsed, tick); }
+        let (due, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|s| s.at.is_due(elapsed, tick));
+        self.pending = pending;
+        due
+    }
+
+    /// Returns `true` if there are no pending changes.
+    pub(crate) fn is_empty(&self) -> bool
+    {
+        self.pending.is_empty()
+    }
+
+    /// Iterates attributes that will be removed from a client after a duration.
+    pub(crate) fn iter_timed_attributes(&self, client_id: u64) -> impl Iterator<Item = VisibilityAttributeId> + '_
+    {
+        self.pending
+            .iter()
+            .filter(move |s| {
+                s.client_id == client_id
+                && s.change == ScheduledChange::Remove
+                && s.at.is_timed()
+            })
+            .map(|s| s.attribute)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Applies scheduled client attribute changes.
+pub(crate) fn apply_scheduled_attributes(
+    time: Res<Time>,
+    server_tick: Res<ServerTick>,
+    id_map: Res<NetworkIdMap>,
+    mut visibility_cache: ResMut<VisibilityCache>,
+    mut client_entities: Query<&mut ClientVisibility>,
+){
+    visibility_cache.apply_scheduled_attributes(&id_map, &mut client_entities, time.elapsed(), **server_tick);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
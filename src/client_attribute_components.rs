@@ -0,0 +1,138 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_replicon::shared::backend::connected_client::NetworkId;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn add_component_attribute<T: VisibilityAttribute + Component>(
+    client_entity: Entity,
+    attributes: &mut ClientAttributes,
+    clients: &Query<(&T, &NetworkId)>,
+){
+    let Ok((attribute, client_id)) = clients.get(client_entity) else { return; };
+    let attribute_id = attribute.attribute_id();
+    record_attribute(attribute, attribute_id);
+    attributes.add_id(Some(client_entity), client_id.get(), attribute_id);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn remove_component_attribute<T: VisibilityAttribute + Component>(
+    client_entity: Entity,
+    attributes: &mut ClientAttributes,
+    clients: &Query<(&T, &NetworkId)>,
+){
+    let Ok((attribute, client_id)) = clients.get(client_entity) else { return; };
+    attributes.remove_id(Some(client_entity), client_id.get(), attribute.attribute_id());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn add_attribute_on_insert<T: VisibilityAttribute + Component>(
+    event: Trigger<OnInsert, T>,
+    mut attributes: ClientAttributes,
+    clients: Query<(&T, &NetworkId)>,
+){
+    add_component_attribute(event.target(), &mut attributes, &clients);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Runs before the component is replaced or removed, so we can access the old attribute.
+fn remove_attribute_on_replace<T: VisibilityAttribute + Component>(
+    event: Trigger<OnReplace, T>,
+    mut attributes: ClientAttributes,
+    clients: Query<(&T, &NetworkId)>,
+){
+    remove_component_attribute(event.target(), &mut attributes, &clients);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn add_attribute_on_network_id<T: VisibilityAttribute + Component>(
+    event: Trigger<OnInsert, NetworkId>,
+    mut attributes: ClientAttributes,
+    clients: Query<(&T, &NetworkId)>,
+){
+    add_component_attribute(event.target(), &mut attributes, &clients);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn remove_attribute_on_network_id<T: VisibilityAttribute + Component>(
+    event: Trigger<OnReplace, NetworkId>,
+    mut attributes: ClientAttributes,
+    clients: Query<(&T, &NetworkId)>,
+){
+    remove_component_attribute(event.target(), &mut attributes, &clients);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn add_attribute_on_reset<T: VisibilityAttribute + Component>(
+    event: Trigger<ClientAttributesReset>,
+    mut attributes: ClientAttributes,
+    clients: Query<(&T, &NetworkId)>,
+){
+    add_component_attribute(event.target(), &mut attributes, &clients);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extends `App` with methods for using [`VisibilityAttribute`] components on client entities.
+pub trait ClientAttributeComponentsAppExt
+{
+    /// Registers a [`VisibilityAttribute`] that can be inserted as a component on client entities.
+    ///
+    /// Client entities are entities with a `NetworkId`. Inserting the component adds its attribute to the client,
+    /// replacing the component replaces the attribute, and removing the component removes the attribute.
+    ///
+    /// Component attributes are re-added after a client is reset (see [`ReconnectPolicy::Reset`]).
+    ///
+    /// Note that attributes added by components can still be removed with [`ClientAttributes::remove`], so you should
+    /// avoid manually adding and removing attributes of types registered here.
+    ///
+    /// Example:
+    /**
+    ```rust
+    #[derive(Component, VisibilityAttribute)]
+    struct InZone(u32);
+
+    app.register_client_attribute_component::<InZone>();
+
+    fn enter_zone(mut c: Commands, client_entity: Entity)
+    {
+        c.entity(client_entity).insert(InZone(3));
+    }
+    ```
+    */
+    fn register_client_attribute_component<T: VisibilityAttribute + Component>(&mut self) -> &mut Self;
+}
+
+impl ClientAttributeComponentsAppExt for App
+{
+    fn register_client_attribute_component<T: VisibilityAttribute + Component>(&mut self) -> &mut Self
+    {
+        self.add_observer(add_attribute_on_insert::<T>)
+            .add_observer(remove_attribute_on_replace::<T>)
+            .add_observer(add_attribute_on_network_id::<T>)
+            .add_observer(remove_attribute_on_network_id::<T>)
+            .add_observer(add_attribute_on_reset::<T>)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
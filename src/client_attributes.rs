@@ -6,9 +6,12 @@ use bevy::prelude::*;
 use bevy::ecs::system::SystemParam;
 use bevy_replicon::prelude::ClientVisibility;
 use bevy_replicon::shared::backend::connected_client::{NetworkId, NetworkIdMap};
+use bevy_replicon::shared::replicon_tick::RepliconTick;
 
 //standard shortcuts
+use std::any::TypeId;
 use std::collections::HashSet;
+use std::time::Duration;
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -40,14 +43,269 @@ impl<'w, 's> ClientAttributes<'w, 's>
     pub fn add<T: VisibilityAttribute>(&mut self, client_id: u64, attribute: T)
     {
         let client_entity = self.id_map.get(&NetworkId::new(client_id)).copied();
-        self.cache.add_client_attribute(&mut self.client_entities.transmute_lens().query(), client_entity, client_id, attribute.attribute_id());
+        let attribute_id = attribute.attribute_id();
+        record_attribute(&attribute, attribute_id);
+        self.cache.add_client_attribute(&mut self.client_entities.transmute_lens().query(), client_entity, client_id, attribute_id);
     }
 
     /// Removes an attribute from a client.
+    ///
+    /// Pending expirations of the attribute from [`Self::add_for`] are cancelled. Attributes granted with
+    /// [`Self::grant`] are kept until their sources are revoked.
     pub fn remove<T: VisibilityAttribute>(&mut self, client_id: u64, attribute: T)
     {
         let client_entity = self.id_map.get(&NetworkId::new(client_id)).copied();
-        self.cache.remove_client_attribute(&mut self.client_entities.transmute_lens().query(), client_entity, client_id, attribute.attribute_id());
+        let attribute_id = attribute.attribute_id();
+        record_attribute(&attribute, attribute_id);
+        self.cache.cancel_timed_removals(client_id, attribute_id);
+        self.cache.remove_client_attribute(&mut self.client_entities.transmute_lens().query(), client_entity, client_id, attribute_id);
+    }
+
+    /// Replaces all of a client's attributes with a new set.
+    ///
+    /// Only the differences between the client's current and new attributes are applied, and visibility conditions
+    /// affected by the differences are evaluated once. This is useful for systems that recompute a client's
+    /// attributes from scratch, since they don't need to track which attributes were added previously.
+    ///
+    /// Attributes that were granted with [`Self::grant`] are kept until their sources are revoked, and pending
+    /// expirations of removed attributes are cancelled (see [`Self::remove`]).
+    ///
+    /// Note that this will remove the builtin [`Global`] and [`Client`] attributes if they are not in `attributes`.
+    /// Use [`Self::sync_type`] to only replace attributes of one type.
+    pub fn sync(&mut self, client_id: u64, attributes: impl IntoIterator<Item = VisibilityAttributeId>)
+    {
+        let client_entity = self.id_map.get(&NetworkId::new(client_id)).copied();
+        self.cache.sync_client_attributes(
+            &mut self.client_entities.transmute_lens().query(),
+            client_entity,
+            client_id,
+            attributes.into_iter().collect(),
+            None
+        );
+    }
+
+    /// Replaces a client's attributes of type `T` with a new set.
+    ///
+    /// Attributes of other types are not affected. See [`Self::sync`].
+    pub fn sync_type<T: VisibilityAttribute>(&mut self, client_id: u64, attributes: impl IntoIterator<Item = T>)
+    {
+        let client_entity = self.id_map.get(&NetworkId::new(client_id)).copied();
+        let attributes = attributes
+            .into_iter()
+            .map(|attribute| {
+                let attribute_id = attribute.attribute_id();
+                record_attribute(&attribute, attribute_id);
+                attribute_id
+            })
+            .collect();
+        self.cache.sync_client_attributes(
+            &mut self.client_entities.transmute_lens().query(),
+            client_entity,
+            client_id,
+            attributes,
+            Some(TypeId::of::<T>())
+        );
+    }
+
+    /// Adds an attribute to a client for a limited time.
+    ///
+    /// The attribute is removed in [`VisibilityUpdateSet`] once `duration` has elapsed according to the `Time`
+    /// resource, starting from the next time [`VisibilityUpdateSet`] runs. Calling this again for the same attribute replaces the previous expiration. Other pending changes
+    /// are not affected, so calling [`Self::add`] will not prevent the attribute from expiring.
+    ///
+    /// If the client's attributes are reset before the attribute expires (see [`ReconnectPolicy::Reset`]), then the
+    /// attribute is re-added after the reset.
+    ///
+    /// Does nothing if the client has no attributes, for example if it disconnected while using
+    /// [`ReconnectPolicy::Reset`].
+    pub fn add_for<T: VisibilityAttribute>(&mut self, client_id: u64, attribute: T, duration: Duration)
+    {
+        if self.cache.client_attributes(client_id).is_none()
+        { tracing::warn!(?client_id, "ignoring timed attribute for unknown client"); return; }
+        let attribute_id = attribute.attribute_id();
+        self.add(client_id, attribute);
+        self.cache.cancel_timed_removals(client_id, attribute_id);
+        self.cache.schedule_client_attribute(ScheduledAttribute{
+            client_id,
+            attribute: attribute_id,
+            change: ScheduledChange::Remove,
+            at: ScheduledAt::After(duration),
+        });
+    }
+
+    /// Adds an attribute to a client when the server reaches a tick.
+    ///
+    /// The attribute is added in [`VisibilityUpdateSet`] after `bevy_replicon` increments the tick, so it takes
+    /// effect in the same replication message as `tick`. If `tick` has already passed then the attribute is added the
+    /// next time [`VisibilityUpdateSet`] runs. Changes scheduled for the same tick are applied in the order they were
+    /// scheduled.
+    ///
+    /// Pending changes are kept when a client's attributes are reset, and dropped when the client disconnects (see
+    /// [`ReconnectPolicy::Reset`]). Does nothing if the client has no attributes.
+    pub fn add_at_tick<T: VisibilityAttribute>(&mut self, client_id: u64, attribute: T, tick: RepliconTick)
+    {
+        let attribute_id = attribute.attribute_id();
+        record_attribute(&attribute, attribute_id);
+        self.cache.schedule_client_attribute(ScheduledAttribute{
+            client_id,
+            attribute: attribute_id,
+            change: ScheduledChange::Add,
+            at: ScheduledAt::Tick(tick),
+        });
+    }
+
+    /// Removes an attribute from a client when the server reaches a tick.
+    ///
+    /// See [`Self::add_at_tick`].
+    pub fn remove_at_tick<T: VisibilityAttribute>(&mut self, client_id: u64, attribute: T, tick: RepliconTick)
+    {
+        let attribute_id = attribute.attribute_id();
+        record_attribute(&attribute, attribute_id);
+        self.cache.schedule_client_attribute(ScheduledAttribute{
+            client_id,
+            attribute: attribute_id,
+            change: ScheduledChange::Remove,
+            at: ScheduledAt::Tick(tick),
+        });
+    }
+
+    /// Grants an attribute to a client from a source.
+    ///
+    /// Granted attributes are reference-counted by source. The attribute stays on the client until all of its sources
+    /// are revoked with [`Self::revoke`] or [`Self::revoke_all_from`]. Granting the same attribute twice from one
+    /// source is the same as granting it once.
+    ///
+    /// Adding an attribute directly (e.g. with [`Self::add`], [`Self::sync_type`], or as a default attribute) counts as
+    /// an implicit source. Calling [`Self::remove`] on a granted attribute only removes the implicit source, and the
+    /// attribute stays on the client until its grants are revoked.
+    ///
+    /// Grants are kept when a client disconnects, and granted attributes are re-added when a client's attributes are
+    /// reset (see [`ReconnectPolicy::Reset`]). Revoke grants from clients that won't reconnect to release them.
+    pub fn grant<T: VisibilityAttribute>(&mut self, client_id: u64, attribute: T, source: impl Into<AttributeSource>)
+    {
+        let client_entity = self.id_map.get(&NetworkId::new(client_id)).copied();
+        let attribute_id = attribute.attribute_id();
+        record_attribute(&attribute, attribute_id);
+        self.cache.grant_client_attribute(
+            &mut self.client_entities.transmute_lens().query(),
+            client_entity,
+            client_id,
+            attribute_id,
+            source.into()
+        );
+    }
+
+    /// Revokes a source's grant of an attribute to a client.
+    ///
+    /// The attribute is removed from the client if it has no remaining sources, unless it was also added directly
+    /// (see [`Self::grant`]). Does nothing if the source did not grant the attribute.
+    pub fn revoke<T: VisibilityAttribute>(&mut self, client_id: u64, attribute: T, source: impl Into<AttributeSource>)
+    {
+        let client_entity = self.id_map.get(&NetworkId::new(client_id)).copied();
+        let attribute_id = attribute.attribute_id();
+        record_attribute(&attribute, attribute_id);
+        self.cache.revoke_client_attribute(
+            &mut self.client_entities.transmute_lens().query(),
+            client_entity,
+            client_id,
+            attribute_id,
+            &source.into()
+        );
+    }
+
+    /// Revokes all attributes granted by a source to all clients.
+    ///
+    /// Attributes are removed from clients if they have no remaining sources, including direct adds (see [`Self::grant`]).
+    pub fn revoke_all_from(&mut self, source: impl Into<AttributeSource>)
+    {
+        let id_map = &self.id_map;
+        self.cache.revoke_all_client_attributes_from(
+            &mut self.client_entities.transmute_lens().query(),
+            |client_id| id_map.get(&NetworkId::new(client_id)).copied(),
+            &source.into()
+        );
+    }
+
+    /// Iterates the sources that granted an attribute to a client.
+    pub fn sources<T: VisibilityAttribute>(&self, client_id: u64, attribute: T) -> impl Iterator<Item = &AttributeSource> + '_
+    {
+        self.cache.iter_attribute_sources(client_id, attribute.attribute_id())
+    }
+
+    /// Gets the client entity of a client.
+    pub(crate) fn client_entity(&self, client_id: u64) -> Option<Entity>
+    {
+        self.id_map.get(&NetworkId::new(client_id)).copied()
+    }
+
+    /// Adds an attribute id to a client with a known client entity.
+    pub(crate) fn add_id(&mut self, client_entity: Option<Entity>, client_id: u64, attribute: VisibilityAttributeId)
+    {
+        self.cache.add_client_attribute(&mut self.client_entities.transmute_lens().query(), client_entity, client_id, attribute);
+    }
+
+    /// Removes an attribute id from a client with a known client entity.
+    pub(crate) fn remove_id(&mut self, client_entity: Option<Entity>, client_id: u64, attribute: VisibilityAttributeId)
+    {
+        self.cache.remove_client_attribute(&mut self.client_entities.transmute_lens().query(), client_entity, client_id, attribute);
+    }
+
+    /// Forces an entity to be visible to a client regardless of the entity's [`VisibilityCondition`].
+    ///
+    /// The override persists until cleared with [`Self::clear_override`] or [`Self::clear_overrides`], the entity
+    /// loses its `Replicated` component or is despawned, or the client disconnects while using
+    /// [`ReconnectPolicy::Reset`]. With [`ReconnectPolicy::Repair`], overrides are kept after a disconnect so they
+    /// can be re-applied when the client reconnects.
+    pub fn force_visible(&mut self, client_id: u64, entity: Entity)
+    {
+        self.set_override(client_id, entity, Some(true));
+    }
+
+    /// Forces an entity to be hidden from a client regardless of the entity's [`VisibilityCondition`].
+    ///
+    /// The override persists until cleared with [`Self::clear_override`] or [`Self::clear_overrides`], the entity
+    /// loses its `Replicated` component or is despawned, or the client disconnects while using
+    /// [`ReconnectPolicy::Reset`]. With [`ReconnectPolicy::Repair`], overrides are kept after a disconnect so they
+    /// can be re-applied when the client reconnects.
+    pub fn force_hidden(&mut self, client_id: u64, entity: Entity)
+    {
+        self.set_override(client_id, entity, Some(false));
+    }
+
+    /// Clears a visibility override set with [`Self::force_visible`] or [`Self::force_hidden`].
+    ///
+    /// The entity's visibility will be determined by its [`VisibilityCondition`].
+    pub fn clear_override(&mut self, client_id: u64, entity: Entity)
+    {
+        self.set_override(client_id, entity, None);
+    }
+
+    /// Clears all visibility overrides of a client.
+    ///
+    /// This is useful for releasing the overrides of clients that won't reconnect when using
+    /// [`ReconnectPolicy::Repair`].
+    pub fn clear_overrides(&mut self, client_id: u64)
+    {
+        let client_entity = self.id_map.get(&NetworkId::new(client_id)).copied();
+        self.cache.clear_visibility_overrides(&mut self.client_entities.transmute_lens().query(), client_entity, client_id);
+    }
+
+    /// Gets a client's visibility override for an entity.
+    pub fn get_override(&self, client_id: u64, entity: Entity) -> Option<bool>
+    {
+        self.cache.visibility_override(client_id, entity)
+    }
+
+    fn set_override(&mut self, client_id: u64, entity: Entity, visibility: Option<bool>)
+    {
+        let client_entity = self.id_map.get(&NetworkId::new(client_id)).copied();
+        self.cache.set_visibility_override(
+            &mut self.client_entities.transmute_lens().query(),
+            client_entity,
+            client_id,
+            entity,
+            visibility
+        );
     }
 
     /// Gets a client's attributes.
@@ -65,6 +323,9 @@ impl<'w, 's> ClientAttributes<'w, 's>
     /// Evaluates a visibility condition against all clients.
     ///
     /// Returns an iterator of clients that evaluate true.
+    ///
+    /// Only client attributes are evaluated, since predicates and parameters depend on an entity. Predicate and
+    /// parameter nodes evaluate to `false` (see [`VisibilityCondition::evaluate`]).
     pub fn evaluate<'b, 'a: 'b>(&'b self, condition: &'a VisibilityCondition) -> impl Iterator<Item = u64> + 'b
     {
         self.cache.iter_client_visibility(condition)
@@ -73,6 +334,8 @@ impl<'w, 's> ClientAttributes<'w, 's>
     /// Evaluates a visibility condition against connected clients.
     ///
     /// Returns an iterator of client ids and last-change ticks for clients that evaluate true.
+    ///
+    /// Predicate and parameter nodes evaluate to `false`. See [`Self::evaluate`].
     pub fn evaluate_connected<'b, 'a: 'b>(
         &'b self,
         condition: &'a VisibilityCondition
@@ -92,12 +355,14 @@ impl<'w, 's> ClientAttributes<'w, 's>
     /// Evaluates a visibility condition against the 'server player' if it exists.
     ///
     /// Returns `None` if the server player doesn't exist or they don't satisfy the visibility condition.
+    ///
+    /// Predicate and parameter nodes evaluate to `false`. See [`Self::evaluate`].
     pub fn evaluate_server_player<'b, 'a: 'b>(
         &'b self,
         condition: &'a VisibilityCondition
     ) -> Option<u64>
     {
-        let Some(server_id) = self.cache.server_id() else { return None; };
+        let server_id = self.cache.server_id()?;
 
         match self.cache.client_visibility(server_id, condition)
         {
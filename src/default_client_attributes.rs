@@ -0,0 +1,99 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+
+//standard shortcuts
+use std::fmt::Debug;
+use std::sync::Arc;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+type AttributeFactory = dyn Fn(u64, Option<&EntityRef>) -> Option<VisibilityAttributeId> + Send + Sync + 'static;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Factory for an attribute added to clients when they start replicating.
+///
+/// See [`VisibilitySettings::with_default_attribute`].
+///
+/// Example:
+/**
+```rust
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct InLobby;
+
+#[derive(VisibilityAttribute)]
+struct ProtocolVersion(u32);
+
+#[derive(Component)]
+struct ClientProtocol(u32);
+
+app.insert_resource(
+    VisibilitySettings::default()
+        .with_default_attribute(DefaultClientAttribute::new(InLobby))
+        .with_default_attribute(DefaultClientAttribute::from_component(|p: &ClientProtocol| ProtocolVersion(p.0)))
+);
+```
+*/
+#[derive(Clone)]
+pub struct DefaultClientAttribute
+{
+    factory: Arc<AttributeFactory>,
+}
+
+impl DefaultClientAttribute
+{
+    /// Makes a factory that gives the same attribute to all clients.
+    pub fn new(attribute: impl VisibilityAttribute) -> Self
+    {
+        let attribute = attribute.attribute_id();
+        Self{ factory: Arc::new(move |_, _| Some(attribute)) }
+    }
+
+    /// Makes a factory that derives an attribute from a client's id.
+    pub fn from_client_id<A: VisibilityAttribute>(factory: impl Fn(u64) -> A + Send + Sync + 'static) -> Self
+    {
+        Self{ factory: Arc::new(move |client_id, _| Some((factory)(client_id).attribute_id())) }
+    }
+
+    /// Makes a factory that derives an attribute from a component on the client entity.
+    ///
+    /// The component must be on the client entity when the client starts replicating (when `ClientVisibility` is
+    /// added). No attribute is added if the component is missing, or if the client is the server player.
+    ///
+    /// To keep an attribute in sync with a component that changes, use
+    /// [`register_client_attribute_component`](ClientAttributeComponentsAppExt::register_client_attribute_component)
+    /// instead.
+    pub fn from_component<C, A>(factory: impl Fn(&C) -> A + Send + Sync + 'static) -> Self
+    where
+        C: Component,
+        A: VisibilityAttribute,
+    {
+        Self{
+            factory: Arc::new(move |_, client_entity| {
+                let component = client_entity?.get::<C>()?;
+                Some((factory)(component).attribute_id())
+            })
+        }
+    }
+
+    /// Creates the attribute for a client.
+    pub(crate) fn create(&self, client_id: u64, client_entity: Option<&EntityRef>) -> Option<VisibilityAttributeId>
+    {
+        (self.factory)(client_id, client_entity)
+    }
+}
+
+impl Debug for DefaultClientAttribute
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        f.debug_struct("DefaultClientAttribute").finish_non_exhaustive()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
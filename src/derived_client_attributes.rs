@@ -0,0 +1,217 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use bevy_replicon::shared::backend::connected_client::NetworkId;
+
+//standard shortcuts
+use std::collections::HashMap;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reference counts for attributes derived from controlled entities.
+///
+/// Multiple controlled entities can derive the same attribute for a client, so attributes are only removed from a
+/// client when no controlled entity derives them anymore.
+#[derive(Resource, Default)]
+struct DerivedClientAttributeCounts
+{
+    /// [ (client id, attribute) : number of controlled entities deriving the attribute ]
+    counts: HashMap<(u64, VisibilityAttributeId), usize>,
+}
+
+impl DerivedClientAttributeCounts
+{
+    fn acquire(&mut self, attributes: &mut ClientAttributes, client_id: u64, attribute: VisibilityAttributeId)
+    {
+        let count = self.counts.entry((client_id, attribute)).or_default();
+        *count += 1;
+        if *count > 1 { return; }
+        attributes.add_id(attributes.client_entity(client_id), client_id, attribute);
+    }
+
+    fn release(&mut self, attributes: &mut ClientAttributes, client_id: u64, attribute: VisibilityAttributeId)
+    {
+        let Some(count) = self.counts.get_mut(&(client_id, attribute)) else { return; };
+        *count -= 1;
+        if *count > 0 { return; }
+        self.counts.remove(&(client_id, attribute));
+        attributes.remove_id(attributes.client_entity(client_id), client_id, attribute);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks attributes derived from component `C`.
+#[derive(Resource)]
+struct DerivedClientAttributeTracker<C: Component, A: VisibilityAttribute>
+{
+    derive: Box<dyn Fn(&C) -> A + Send + Sync + 'static>,
+    /// [ controlled entity : (client id, derived attribute) ]
+    entities: EntityHashMap<(u64, VisibilityAttributeId)>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+type ChangedControlledFilter<C> = Or<(Changed<C>, Changed<ControlledBy>)>;
+
+fn update_derived_client_attributes<C: Component, A: VisibilityAttribute>(
+    mut tracker: ResMut<DerivedClientAttributeTracker<C, A>>,
+    mut counts: ResMut<DerivedClientAttributeCounts>,
+    mut attributes: ClientAttributes,
+    changed: Query<(Entity, &C, &ControlledBy), ChangedControlledFilter<C>>,
+){
+    for (entity, component, controlled_by) in changed.iter()
+    {
+        let attribute = (tracker.derive)(component);
+        let attribute_id = attribute.attribute_id();
+        record_attribute(&attribute, attribute_id);
+
+        let derived = (controlled_by.0, attribute_id);
+        match tracker.entities.insert(entity, derived)
+        {
+            Some(prev) if prev == derived => continue,
+            Some((client_id, attribute_id)) => counts.release(&mut attributes, client_id, attribute_id),
+            None => (),
+        }
+        counts.acquire(&mut attributes, derived.0, derived.1);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Runs when `C` or [`ControlledBy`] is removed or despawned.
+///
+/// Replacements are diffed by [`update_derived_client_attributes`], so re-inserting an equal component doesn't
+/// release and re-add the attribute.
+fn release_derived_client_attribute<C: Component, A: VisibilityAttribute, E: Component>(
+    event: Trigger<OnRemove, E>,
+    mut tracker: ResMut<DerivedClientAttributeTracker<C, A>>,
+    mut counts: ResMut<DerivedClientAttributeCounts>,
+    mut attributes: ClientAttributes,
+){
+    let Some((client_id, attribute_id)) = tracker.entities.remove(&event.target()) else { return; };
+    counts.release(&mut attributes, client_id, attribute_id);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn readd_derived_client_attributes(
+    client_entity: Entity,
+    client_id: u64,
+    counts: &DerivedClientAttributeCounts,
+    attributes: &mut ClientAttributes,
+){
+    for (_, attribute_id) in counts.counts.keys().filter(|(id, _)| *id == client_id)
+    {
+        attributes.add_id(Some(client_entity), client_id, *attribute_id);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn readd_derived_client_attributes_on_reset(
+    event: Trigger<ClientAttributesReset>,
+    counts: Res<DerivedClientAttributeCounts>,
+    mut attributes: ClientAttributes,
+){
+    readd_derived_client_attributes(event.target(), event.client_id, &counts, &mut attributes);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn readd_derived_client_attributes_on_network_id(
+    event: Trigger<OnInsert, NetworkId>,
+    counts: Res<DerivedClientAttributeCounts>,
+    mut attributes: ClientAttributes,
+    client_ids: Query<&NetworkId>,
+){
+    let Ok(client_id) = client_ids.get(event.target()) else { return; };
+    readd_derived_client_attributes(event.target(), client_id.get(), &counts, &mut attributes);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component that links an entity to the client that controls it.
+///
+/// Components on controlled entities can be used to derive client attributes with
+/// [`DerivedClientAttributesAppExt::derive_client_attributes`].
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ControlledBy(pub u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extends `App` with methods for deriving client attributes from components on controlled entities.
+pub trait DerivedClientAttributesAppExt
+{
+    /// Keeps client attributes in sync with component `C` on entities with a [`ControlledBy`] component.
+    ///
+    /// The attribute returned by `derive` is added to the controlling client. It is removed when `C` or
+    /// [`ControlledBy`] is removed, when the entity is despawned, or when control is transferred to a different
+    /// client. If several controlled entities derive the same attribute for a client, then the attribute is only
+    /// removed once none of them derive it.
+    ///
+    /// Changes to `C` and [`ControlledBy`] are detected in `PostUpdate` before [`VisibilityUpdateSet`].
+    ///
+    /// Calling this again with the same `C` and `A` replaces the previous `derive` callback.
+    ///
+    /// Example:
+    /**
+    ```rust
+    #[derive(Component)]
+    struct Team(u32);
+
+    #[derive(VisibilityAttribute)]
+    struct InTeam(u32);
+
+    app.derive_client_attributes(|team: &Team| InTeam(team.0));
+
+    fn spawn_player(mut c: Commands, client_id: u64)
+    {
+        c.spawn((Team(1), ControlledBy(client_id)));
+    }
+    ```
+    */
+    fn derive_client_attributes<C: Component, A: VisibilityAttribute>(
+        &mut self,
+        derive: impl Fn(&C) -> A + Send + Sync + 'static
+    ) -> &mut Self;
+}
+
+impl DerivedClientAttributesAppExt for App
+{
+    fn derive_client_attributes<C: Component, A: VisibilityAttribute>(
+        &mut self,
+        derive: impl Fn(&C) -> A + Send + Sync + 'static
+    ) -> &mut Self
+    {
+        if let Some(mut tracker) = self.world_mut().get_resource_mut::<DerivedClientAttributeTracker<C, A>>()
+        {
+            tracker.derive = Box::new(derive);
+            return self;
+        }
+
+        if !self.world().contains_resource::<DerivedClientAttributeCounts>()
+        {
+            self.init_resource::<DerivedClientAttributeCounts>()
+                .add_observer(readd_derived_client_attributes_on_reset)
+                .add_observer(readd_derived_client_attributes_on_network_id);
+        }
+
+        self.insert_resource(DerivedClientAttributeTracker::<C, A>{
+                derive: Box::new(derive),
+                entities: EntityHashMap::default(),
+            })
+            .add_observer(release_derived_client_attribute::<C, A, C>)
+            .add_observer(release_derived_client_attribute::<C, A, ControlledBy>)
+            .add_systems(PostUpdate, update_derived_client_attributes::<C, A>.before(VisibilityUpdateSet))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,109 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Derives visibility conditions from component `C`.
+#[derive(Resource)]
+struct VisibilityConditionDeriver<C: Component>
+{
+    derive: Box<dyn Fn(&C) -> VisibilityCondition + Send + Sync + 'static>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+type DerivedLayerQueryData<C> = (Entity, &'static C, Option<&'static mut VisibilityLayer<C>>);
+
+fn update_derived_visibility_conditions<C: Component>(
+    mut c: Commands,
+    deriver: Res<VisibilityConditionDeriver<C>>,
+    mut changed: Query<DerivedLayerQueryData<C>, Changed<C>>,
+){
+    for (entity, component, layer) in changed.iter_mut()
+    {
+        let derived = (deriver.derive)(component);
+        match layer
+        {
+            Some(mut layer) => { if *layer.condition() != derived { layer.set(derived); } }
+            None => { c.entity(entity).insert(VisibilityLayer::<C>::new(derived)); }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Runs when `C` is removed or its entity is despawned.
+fn remove_derived_visibility_condition<C: Component>(event: Trigger<OnRemove, C>, mut c: Commands)
+{
+    c.entity(event.target()).try_remove::<VisibilityLayer<C>>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extends `App` with methods for deriving entity visibility conditions from components.
+pub trait DerivedVisibilityConditionsAppExt
+{
+    /// Keeps a [`VisibilityCondition`] on every entity with component `C`.
+    ///
+    /// The condition returned by `derive` is written to the entity's `VisibilityLayer<C>` when `C` is inserted, and
+    /// updated whenever `C` changes. Changes are detected in `PostUpdate` before [`VisibilityLayerSet`], so they are
+    /// applied to client visibility in the same tick. The layer is removed when `C` is removed.
+    ///
+    /// Since the derived condition is a [`VisibilityLayer`], it is combined with the entity's other layers, and you
+    /// should not manually insert conditions on entities with `C`.
+    ///
+    /// Calling this again with the same `C` replaces the previous `derive` callback.
+    ///
+    /// Example:
+    /**
+    ```rust
+    #[derive(Component)]
+    struct Owner(u64);
+
+    #[derive(VisibilityAttribute, Default, PartialEq)]
+    struct IsAdmin;
+
+    app.derive_visibility_condition::<Owner>(|owner| vis!(or(Client(owner.0), IsAdmin)));
+
+    fn spawn_item(mut c: Commands, owner: u64)
+    {
+        c.spawn((Replicated, Owner(owner)));
+    }
+    ```
+    */
+    fn derive_visibility_condition<C: Component>(
+        &mut self,
+        derive: impl Fn(&C) -> VisibilityCondition + Send + Sync + 'static
+    ) -> &mut Self;
+}
+
+impl DerivedVisibilityConditionsAppExt for App
+{
+    fn derive_visibility_condition<C: Component>(
+        &mut self,
+        derive: impl Fn(&C) -> VisibilityCondition + Send + Sync + 'static
+    ) -> &mut Self
+    {
+        if let Some(mut deriver) = self.world_mut().get_resource_mut::<VisibilityConditionDeriver<C>>()
+        {
+            deriver.derive = Box::new(derive);
+            return self;
+        }
+
+        self.insert_resource(VisibilityConditionDeriver::<C>{ derive: Box::new(derive) })
+            .register_visibility_layer::<C>()
+            .add_observer(remove_derived_visibility_condition::<C>)
+            .add_systems(PostUpdate, update_derived_visibility_conditions::<C>.before(VisibilityLayerSet))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,227 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_replicon::server::server_tick::ServerTick;
+use bevy_replicon::shared::replicon_tick::RepliconTick;
+
+//standard shortcuts
+use std::collections::{HashMap, HashSet};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds the condition for an entity observed by a set of teams.
+fn fog_condition(seen: Option<&SeenByTeams>) -> VisibilityCondition
+{
+    let mut teams: Vec<u64> = seen.into_iter().flat_map(|seen| seen.teams()).collect();
+    teams.sort_unstable();
+
+    let mut condition = VisibilityCondition::empty();
+    for team in teams
+    {
+        condition.or(InTeam(team));
+    }
+    condition
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The number of ticks after which observations expire. See [`FogOfWarPlugin::stale_after`].
+#[derive(Resource)]
+struct FogOfWarStaleAfter(u32);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn insert_fog_of_war(event: Trigger<OnInsert, FogOfWar>, mut c: Commands, seen: Query<&SeenByTeams>)
+{
+    let entity = event.target();
+    c.entity(entity).insert(VisibilityLayer::<FogOfWar>::new(fog_condition(seen.get(entity).ok())));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn remove_fog_of_war(event: Trigger<OnRemove, FogOfWar>, mut c: Commands)
+{
+    c.entity(event.target()).try_remove::<(SeenByTeams, VisibilityLayer<FogOfWar>)>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Applies new observations, drops stale observations, and updates the layers of affected entities.
+fn update_team_observations(
+    server_tick: Res<ServerTick>,
+    stale_after: Res<FogOfWarStaleAfter>,
+    mut expired: EventWriter<TeamObservationExpired>,
+    mut fogged: Query<(Entity, &mut SeenByTeams, &mut VisibilityLayer<FogOfWar>), With<FogOfWar>>,
+){
+    let tick = **server_tick;
+    let expires = tick + stale_after.0.max(1);
+
+    for (entity, mut seen, mut layer) in fogged.iter_mut()
+    {
+        // Avoid triggering change detection on entities without new or expired observations.
+        let seen = seen.bypass_change_detection();
+        let mut dirty = false;
+
+        for team in seen.reported.drain()
+        {
+            if seen.teams.insert(team, expires).is_none() { dirty = true; }
+        }
+
+        seen.teams.retain(
+            |team, expires|
+            {
+                if tick < *expires { return true; }
+                tracing::trace!(?entity, ?team, "team observation expired");
+                expired.write(TeamObservationExpired{ team: *team, entity });
+                dirty = true;
+                false
+            }
+        );
+
+        if !dirty { continue; }
+        layer.set(fog_condition(Some(seen)));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component with the teams observing a [`FogOfWar`] entity.
+///
+/// Gameplay reports observations with [`Self::observe`] (e.g. from sight-radius checks). An observation lasts until
+/// [`FogOfWarPlugin::stale_after`] ticks have passed without it being reported again, so teams should report
+/// observations every tick. A [`TeamObservationExpired`] event is sent when an observation expires.
+///
+/// Inserted automatically with [`FogOfWar`].
+#[derive(Component, Debug, Default, Clone)]
+pub struct SeenByTeams
+{
+    /// [ team : tick when the observation expires ]
+    teams: HashMap<u64, RepliconTick>,
+    /// Teams that reported observations since observations were last applied.
+    reported: HashSet<u64>,
+}
+
+impl SeenByTeams
+{
+    /// Reports that a team observes the entity.
+    ///
+    /// Observations are applied in `PostUpdate`. See [`FogOfWarPlugin`].
+    pub fn observe(&mut self, team: u64)
+    {
+        self.reported.insert(team);
+    }
+
+    /// Returns `true` if a team currently observes the entity.
+    pub fn is_observed_by(&self, team: u64) -> bool
+    {
+        self.teams.contains_key(&team)
+    }
+
+    /// Iterates the teams that currently observe the entity.
+    pub fn teams(&self) -> impl Iterator<Item = u64> + '_
+    {
+        self.teams.keys().copied()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Event sent when a team's observation of a [`FogOfWar`] entity expires.
+///
+/// The entity is hidden from the team's clients unless another team they belong to still observes it.
+#[derive(Event, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TeamObservationExpired
+{
+    /// The team that stopped observing the entity.
+    pub team: u64,
+    /// The entity.
+    pub entity: Entity,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Attribute for clients that share their team's vision in [`FogOfWarPlugin`].
+#[derive(VisibilityAttribute, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct InTeam(pub u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component for `Replicated` entities that are visible to the teams observing them.
+///
+/// The entity's `VisibilityLayer<FogOfWar>` is set to the [`InTeam`] attributes of all teams in the entity's
+/// [`SeenByTeams`], and is combined with the entity's other [`VisibilityLayers`](VisibilityLayer). Entities that no
+/// team observes are hidden. The layer and observations are removed when this component is removed.
+///
+/// To always show units to their own team, report observations of them from their own team.
+#[derive(Component, Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[require(SeenByTeams)]
+pub struct FogOfWar;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Plugin for team-shared fog of war.
+///
+/// Gameplay reports observations to each [`FogOfWar`] entity's [`SeenByTeams`], and the entity is visible to the
+/// clients with [`InTeam`] attributes for the teams that currently observe it. Clients must be given their `InTeam`
+/// attributes separately (e.g. with [`ClientAttributes::add`]).
+///
+/// Observations are applied in `PostUpdate` before [`VisibilityLayerSet`] and after `bevy_replicon`'s server tick
+/// is incremented, so observations reported during `Update` take effect in the same tick.
+///
+/// Must be added after [`VisibilityAttributesPlugin`].
+///
+/// Example:
+/**
+```rust
+app.add_plugins(FogOfWarPlugin{ stale_after: 5 });
+
+fn spawn_unit(mut c: Commands)
+{
+    c.spawn((Replicated, Unit, FogOfWar));
+}
+
+fn sight_checks(viewers: Query<(&Team, &Transform)>, mut units: Query<(&mut SeenByTeams, &Transform)>)
+{
+    for (viewer_team, viewer_transform) in viewers.iter()
+    {
+        for (mut seen, transform) in units.iter_mut()
+        {
+            if viewer_transform.translation.distance(transform.translation) > SIGHT_RADIUS { continue; }
+            seen.observe(viewer_team.0);
+        }
+    }
+}
+```
+*/
+#[derive(Debug, Clone)]
+pub struct FogOfWarPlugin
+{
+    /// The number of ticks after which an observation is dropped if it is not reported again.
+    ///
+    /// Values less than `1` are treated as `1`, so observations must be reported every tick.
+    pub stale_after: u32,
+}
+
+impl Plugin for FogOfWarPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.add_event::<TeamObservationExpired>()
+            .insert_resource(FogOfWarStaleAfter(self.stale_after))
+            .register_visibility_layer::<FogOfWar>()
+            .add_observer(insert_fog_of_war)
+            .add_observer(remove_fog_of_war)
+            .add_systems(PostUpdate,
+                update_team_observations
+                    .after(bevy_replicon::server::increment_tick)
+                    .before(VisibilityLayerSet)
+            );
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -5,24 +5,64 @@
 use crate as bevy_replicon_attributes;
 
 //module tree
+mod attribute_collisions;
+mod attribute_grants;
+mod attribute_schedule;
 mod builtin_attributes;
+mod client_attribute_components;
 mod client_attributes;
+mod default_client_attributes;
+mod derived_client_attributes;
+mod derived_visibility_conditions;
+mod fog_of_war;
+mod room_graph;
 mod server_event_sender;
+mod spatial_grid;
 mod visibility_attribute;
 mod visibility_attributes_plugin;
+mod visibility_budget;
 mod visibility_cache;
 mod visibility_condition;
 mod visibility_condition_constructors;
+mod visibility_params;
+mod visibility_predicates;
+mod visibility_presets;
+mod visibility_propagation;
+mod visibility_layers;
+mod visibility_linger;
+#[cfg(feature = "rules")]
+mod visibility_rules;
+mod visibility_settings;
 
 //API exports
+pub use crate::attribute_collisions::*;
+pub use crate::attribute_grants::*;
+pub(crate) use crate::attribute_schedule::*;
 pub use crate::builtin_attributes::*;
+pub use crate::client_attribute_components::*;
 pub use crate::client_attributes::*;
+pub use crate::default_client_attributes::*;
+pub use crate::derived_client_attributes::*;
+pub use crate::derived_visibility_conditions::*;
+pub use crate::fog_of_war::*;
+pub use crate::room_graph::*;
 pub use crate::server_event_sender::*;
+pub use crate::spatial_grid::*;
 pub use crate::visibility_attribute::*;
 pub use crate::visibility_attributes_plugin::*;
+pub use crate::visibility_budget::*;
 pub(crate) use crate::visibility_cache::*;
 pub use crate::visibility_condition::*;
 pub use crate::visibility_condition_constructors::*;
+pub use crate::visibility_params::*;
+pub use crate::visibility_predicates::*;
+pub use crate::visibility_presets::*;
+pub use crate::visibility_propagation::*;
+pub use crate::visibility_layers::*;
+pub use crate::visibility_linger::*;
+#[cfg(feature = "rules")]
+pub use crate::visibility_rules::*;
+pub use crate::visibility_settings::*;
 
 pub use bevy_replicon_attributes_derive::*;
 
@@ -0,0 +1,440 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::ecs::entity::{EntityHashMap, EntityHashSet};
+use bevy::prelude::*;
+use bevy_replicon::prelude::Replicated;
+
+//standard shortcuts
+use std::collections::{HashMap, HashSet, VecDeque};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds the condition for entities in a room.
+fn room_condition(graph: &RoomGraph, room: u64) -> VisibilityCondition
+{
+    let mut rooms: Vec<u64> = graph.reachable(room).into_iter().collect();
+    rooms.sort_unstable();
+
+    let mut condition = VisibilityCondition::empty();
+    for room in rooms
+    {
+        condition.or(InRoom(room));
+    }
+    condition
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks the `Replicated` entities in each room.
+#[derive(Resource, Default)]
+struct RoomOccupants
+{
+    /// [ room : [ entity ] ]
+    rooms: HashMap<u64, EntityHashSet>,
+}
+
+impl RoomOccupants
+{
+    fn remove(&mut self, room: u64, entity: Entity)
+    {
+        let Some(entities) = self.rooms.get_mut(&room) else { return; };
+        entities.remove(&entity);
+        if entities.is_empty() { self.rooms.remove(&room); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks the rooms of room avatars.
+#[derive(Resource, Default)]
+struct RoomAvatars
+{
+    /// [ avatar entity : (client id, room) ]
+    avatars: EntityHashMap<(u64, u64)>,
+    /// [ client id : [ avatar entity ] ]
+    clients: HashMap<u64, EntityHashSet>,
+}
+
+impl RoomAvatars
+{
+    /// Inserts or updates an avatar.
+    ///
+    /// Returns the avatar's previous client and room.
+    fn insert(&mut self, entity: Entity, client_id: u64, room: u64) -> Option<(u64, u64)>
+    {
+        let prev = self.avatars.insert(entity, (client_id, room));
+        if let Some((prev_client, _)) = prev
+        {
+            if prev_client != client_id { self.remove_client_avatar(prev_client, entity); }
+        }
+        self.clients.entry(client_id).or_default().insert(entity);
+        prev
+    }
+
+    /// Removes an avatar.
+    ///
+    /// Returns the avatar's client and room.
+    fn remove(&mut self, entity: Entity) -> Option<(u64, u64)>
+    {
+        let (client_id, room) = self.avatars.remove(&entity)?;
+        self.remove_client_avatar(client_id, entity);
+        Some((client_id, room))
+    }
+
+    fn remove_client_avatar(&mut self, client_id: u64, entity: Entity)
+    {
+        let Some(avatars) = self.clients.get_mut(&client_id) else { return; };
+        avatars.remove(&entity);
+        if avatars.is_empty() { self.clients.remove(&client_id); }
+    }
+
+    /// Collects the rooms of a client's avatars.
+    fn client_rooms(&self, client_id: u64) -> impl Iterator<Item = InRoom> + '_
+    {
+        self.clients
+            .get(&client_id)
+            .into_iter()
+            .flat_map(|avatars| avatars.iter())
+            .filter_map(|entity| self.avatars.get(entity))
+            .map(|(_, room)| InRoom(*room))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn insert_current_room(
+    event: Trigger<OnInsert, (CurrentRoom, Replicated)>,
+    mut c: Commands,
+    graph: Res<RoomGraph>,
+    mut occupants: ResMut<RoomOccupants>,
+    rooms: Query<&CurrentRoom, With<Replicated>>,
+){
+    let entity = event.target();
+    let Ok(room) = rooms.get(entity) else { return; };
+    occupants.rooms.entry(room.0).or_default().insert(entity);
+    c.entity(entity).insert(VisibilityLayer::<CurrentRoom>::new(room_condition(&graph, room.0)));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn replace_current_room(
+    event: Trigger<OnReplace, CurrentRoom>,
+    mut occupants: ResMut<RoomOccupants>,
+    rooms: Query<&CurrentRoom>,
+){
+    let entity = event.target();
+    let Ok(room) = rooms.get(entity) else { return; };
+    occupants.remove(room.0, entity);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn remove_current_room(event: Trigger<OnRemove, CurrentRoom>, mut c: Commands, replicated: Query<(), With<Replicated>>)
+{
+    if !replicated.contains(event.target()) { return; }
+    c.entity(event.target()).try_remove::<VisibilityLayer<CurrentRoom>>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Entities that stop being replicated are no longer occupants of their rooms.
+fn remove_replicated_occupant(
+    event: Trigger<OnRemove, Replicated>,
+    mut c: Commands,
+    mut occupants: ResMut<RoomOccupants>,
+    rooms: Query<&CurrentRoom>,
+){
+    let entity = event.target();
+    let Ok(room) = rooms.get(entity) else { return; };
+    occupants.remove(room.0, entity);
+    c.entity(entity).try_remove::<VisibilityLayer<CurrentRoom>>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Updates the conditions of entities in rooms whose reachable rooms changed.
+fn update_dirty_rooms(mut c: Commands, mut graph: ResMut<RoomGraph>, occupants: Res<RoomOccupants>)
+{
+    if graph.dirty.is_empty() { return; }
+
+    for room in std::mem::take(&mut graph.dirty)
+    {
+        let Some(entities) = occupants.rooms.get(&room) else { continue; };
+        let condition = room_condition(&graph, room);
+        for entity in entities.iter()
+        {
+            c.entity(*entity).try_insert(VisibilityLayer::<CurrentRoom>::new(condition.clone()));
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn sync_client_rooms(avatars: &RoomAvatars, attributes: &mut ClientAttributes, client_id: u64)
+{
+    attributes.sync_type(client_id, avatars.client_rooms(client_id));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+type ChangedRoomAvatarFilter = Or<(Changed<RoomAvatar>, Changed<CurrentRoom>)>;
+
+fn update_room_avatars(
+    mut avatars: ResMut<RoomAvatars>,
+    mut attributes: ClientAttributes,
+    mut removed_avatars: RemovedComponents<RoomAvatar>,
+    mut removed_rooms: RemovedComponents<CurrentRoom>,
+    changed: Query<(Entity, &RoomAvatar, &CurrentRoom), ChangedRoomAvatarFilter>,
+    current: Query<(), (With<RoomAvatar>, With<CurrentRoom>)>,
+){
+    let mut dirty = HashSet::<u64>::default();
+
+    for entity in removed_avatars.read().chain(removed_rooms.read())
+    {
+        if current.contains(entity) { continue; }
+        let Some((client_id, _)) = avatars.remove(entity) else { continue; };
+        dirty.insert(client_id);
+    }
+
+    for (entity, avatar, room) in changed.iter()
+    {
+        let current = (avatar.0, room.0);
+        match avatars.insert(entity, avatar.0, room.0)
+        {
+            Some(prev) if prev == current => continue,
+            Some((prev_client, _)) => { dirty.insert(prev_client); }
+            None => (),
+        }
+        dirty.insert(avatar.0);
+    }
+
+    for client_id in dirty
+    {
+        sync_client_rooms(&avatars, &mut attributes, client_id);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn resync_room_avatars_on_reset(
+    event: Trigger<ClientAttributesReset>,
+    avatars: Res<RoomAvatars>,
+    mut attributes: ClientAttributes,
+){
+    sync_client_rooms(&avatars, &mut attributes, event.client_id);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Graph of rooms connected by edges (e.g. doors) that can be opened and closed.
+///
+/// Entities in a room are visible to clients with avatars in rooms reachable within
+/// [`max_hops`](Self::max_hops) open edges. When an edge is toggled, only the conditions of entities in rooms whose
+/// reachable rooms changed are updated.
+///
+/// Rooms without edges are only visible from themselves.
+///
+/// See [`RoomGraphPlugin`].
+#[derive(Resource, Debug, Default)]
+pub struct RoomGraph
+{
+    max_hops: u32,
+    /// [ room : [ adjacent room : is open ] ]
+    edges: HashMap<u64, HashMap<u64, bool>>,
+    /// Rooms whose entities need new conditions.
+    dirty: HashSet<u64>,
+}
+
+impl RoomGraph
+{
+    /// Makes a new room graph.
+    pub fn new(max_hops: u32) -> Self
+    {
+        Self{ max_hops, ..Default::default() }
+    }
+
+    /// Gets the max number of open edges between a room and the rooms that can see it.
+    pub fn max_hops(&self) -> u32
+    {
+        self.max_hops
+    }
+
+    /// Connects two rooms with an edge.
+    ///
+    /// Replaces the edge's open state if the rooms are already connected.
+    pub fn connect(&mut self, a: u64, b: u64, open: bool)
+    {
+        if a == b { return; }
+        match self.edge(a, b)
+        {
+            Some(_) => self.set_open(a, b, open),
+            None =>
+            {
+                self.edges.entry(a).or_default().insert(b, false);
+                self.edges.entry(b).or_default().insert(a, false);
+                self.set_open(a, b, open);
+            }
+        }
+    }
+
+    /// Removes the edge between two rooms.
+    pub fn disconnect(&mut self, a: u64, b: u64)
+    {
+        self.set_open(a, b, false);
+        for (room, other) in [(a, b), (b, a)]
+        {
+            let Some(edges) = self.edges.get_mut(&room) else { continue; };
+            edges.remove(&other);
+            if edges.is_empty() { self.edges.remove(&room); }
+        }
+    }
+
+    /// Opens or closes the edge between two rooms.
+    ///
+    /// Does nothing if the rooms are not connected.
+    pub fn set_open(&mut self, a: u64, b: u64, open: bool)
+    {
+        if self.edge(a, b) != Some(!open) { return; }
+
+        // Rooms affected by the edge are found while the edge is open.
+        if !open { self.mark_affected(a, b); }
+        self.edges.get_mut(&a).unwrap().insert(b, open);
+        self.edges.get_mut(&b).unwrap().insert(a, open);
+        if open { self.mark_affected(a, b); }
+    }
+
+    /// Gets the open state of the edge between two rooms.
+    ///
+    /// Returns `None` if the rooms are not connected.
+    pub fn edge(&self, a: u64, b: u64) -> Option<bool>
+    {
+        self.edges.get(&a)?.get(&b).copied()
+    }
+
+    /// Gets the rooms reachable from a room within [`max_hops`](Self::max_hops) open edges.
+    ///
+    /// Includes the room itself.
+    pub fn reachable(&self, room: u64) -> HashSet<u64>
+    {
+        self.within_hops(room, self.max_hops)
+    }
+
+    fn within_hops(&self, room: u64, hops: u32) -> HashSet<u64>
+    {
+        let mut reached = HashSet::from([room]);
+        let mut queue = VecDeque::from([(room, 0)]);
+        while let Some((room, depth)) = queue.pop_front()
+        {
+            if depth >= hops { continue; }
+            let Some(edges) = self.edges.get(&room) else { continue; };
+            for (next, _) in edges.iter().filter(|(_, open)| **open)
+            {
+                if reached.insert(*next) { queue.push_back((*next, depth + 1)); }
+            }
+        }
+        reached
+    }
+
+    /// Marks rooms whose reachable rooms depend on an open edge.
+    fn mark_affected(&mut self, a: u64, b: u64)
+    {
+        let Some(hops) = self.max_hops.checked_sub(1) else { return; };
+        let affected = self.within_hops(a, hops).into_iter().chain(self.within_hops(b, hops));
+        self.dirty.extend(affected.collect::<Vec<_>>());
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Attribute for rooms added to clients by [`RoomGraphPlugin`].
+///
+/// Clients have the attribute for the rooms of their [`RoomAvatar`] entities.
+///
+/// The attribute's inner id is the room id.
+#[derive(VisibilityAttribute, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[visibility_attribute(packed)]
+pub struct InRoom(pub u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component for entities that are in a room of the [`RoomGraph`].
+///
+/// The `VisibilityLayer<CurrentRoom>` of `Replicated` entities with this component is set to the [`InRoom`]
+/// attributes of all rooms that can see the entity's room, and is combined with the entity's other
+/// [`VisibilityLayers`](VisibilityLayer). The layer is removed when this component or `Replicated` is removed.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CurrentRoom(pub u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component for entities that determine which rooms a client is in.
+///
+/// The client gets the [`InRoom`] attribute for the entity's [`CurrentRoom`]. If a client has multiple avatars then
+/// it is in the rooms of all of them.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RoomAvatar(pub u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Plugin for room-based visibility.
+///
+/// Use the [`RoomGraph`] resource to connect rooms and open or close the edges between them.
+///
+/// Must be added after [`VisibilityAttributesPlugin`].
+///
+/// Example:
+/**
+```rust
+app.add_plugins(RoomGraphPlugin{ max_hops: 1 });
+
+fn setup_level(mut graph: ResMut<RoomGraph>)
+{
+    graph.connect(HALL, KITCHEN, true);
+    graph.connect(HALL, CELLAR, false);
+}
+
+fn spawn_player(mut c: Commands, client_id: u64)
+{
+    c.spawn((Replicated, Player, RoomAvatar(client_id), CurrentRoom(HALL)));
+}
+
+fn open_cellar(mut graph: ResMut<RoomGraph>)
+{
+    graph.set_open(HALL, CELLAR, true);
+}
+```
+*/
+#[derive(Debug, Clone)]
+pub struct RoomGraphPlugin
+{
+    /// The max number of open edges between a client's room and the rooms it can see into.
+    ///
+    /// With `0`, clients can only see entities in their own rooms.
+    pub max_hops: u32,
+}
+
+impl Plugin for RoomGraphPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.insert_resource(RoomGraph::new(self.max_hops))
+            .init_resource::<RoomOccupants>()
+            .init_resource::<RoomAvatars>()
+            .register_visibility_layer::<CurrentRoom>()
+            .add_observer(insert_current_room)
+            .add_observer(replace_current_room)
+            .add_observer(remove_current_room)
+            .add_observer(remove_replicated_occupant)
+            .add_observer(resync_room_avatars_on_reset)
+            .add_systems(PostUpdate,
+                (update_room_avatars, update_dirty_rooms).before(VisibilityLayerSet)
+            );
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -55,7 +55,6 @@ impl<'w, T: Event + Clone> ServerEventSender<'w, T>
                                     event : event.clone(),
                                 }
                             )
-                            .into_iter()
                     )
             );
     }
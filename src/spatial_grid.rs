@@ -0,0 +1,388 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::ecs::entity::{EntityHashMap, EntityHashSet};
+use bevy::prelude::*;
+use bevy_replicon::prelude::Replicated;
+
+//standard shortcuts
+use std::collections::{HashMap, HashSet};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Packs one cell coordinate into 21 bits.
+fn pack_coordinate(coordinate: i32) -> u64
+{
+    (coordinate as u32 & 0x1F_FFFF) as u64
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Returns `true` if a grid's cell size is usable.
+fn valid_cell_size(grid: &SpatialGrid) -> bool
+{
+    grid.cell_size.is_finite() && grid.cell_size > 0.0
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn has_valid_grid(grid: Res<SpatialGrid>) -> bool
+{
+    valid_cell_size(&grid)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks the cells of grid viewers.
+#[derive(Resource, Default)]
+struct GridViewers
+{
+    /// [ viewer entity : (client id, cell) ]
+    viewers: EntityHashMap<(u64, IVec3)>,
+    /// [ client id : [ viewer entity ] ]
+    clients: HashMap<u64, EntityHashSet>,
+}
+
+impl GridViewers
+{
+    /// Inserts or updates a viewer.
+    ///
+    /// Returns the viewer's previous client and cell.
+    fn insert(&mut self, entity: Entity, client_id: u64, cell: IVec3) -> Option<(u64, IVec3)>
+    {
+        let prev = self.viewers.insert(entity, (client_id, cell));
+        if let Some((prev_client, _)) = prev
+        {
+            if prev_client != client_id { self.remove_client_viewer(prev_client, entity); }
+        }
+        self.clients.entry(client_id).or_default().insert(entity);
+        prev
+    }
+
+    /// Removes a viewer.
+    ///
+    /// Returns the viewer's client and cell.
+    fn remove(&mut self, entity: Entity) -> Option<(u64, IVec3)>
+    {
+        let (client_id, cell) = self.viewers.remove(&entity)?;
+        self.remove_client_viewer(client_id, entity);
+        Some((client_id, cell))
+    }
+
+    fn remove_client_viewer(&mut self, client_id: u64, entity: Entity)
+    {
+        let Some(viewers) = self.clients.get_mut(&client_id) else { return; };
+        viewers.remove(&entity);
+        if viewers.is_empty() { self.clients.remove(&client_id); }
+    }
+
+    /// Collects the cells visible to a client from all of its viewers.
+    fn visible_cells(&self, grid: &SpatialGrid, client_id: u64) -> HashSet<InCell>
+    {
+        let mut cells = HashSet::default();
+        let Some(viewers) = self.clients.get(&client_id) else { return cells; };
+        for (_, cell) in viewers.iter().filter_map(|entity| self.viewers.get(entity))
+        {
+            cells.extend(grid.cells_in_view(*cell));
+        }
+        cells
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn sync_client_cells(grid: &SpatialGrid, viewers: &GridViewers, attributes: &mut ClientAttributes, client_id: u64)
+{
+    attributes.sync_type(client_id, viewers.visible_cells(grid, client_id));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+type ChangedGridViewerFilter = Or<(Changed<GridViewer>, Changed<Transform>)>;
+
+fn update_grid_viewers(
+    grid: Res<SpatialGrid>,
+    mut viewers: ResMut<GridViewers>,
+    mut attributes: ClientAttributes,
+    mut removed: RemovedComponents<GridViewer>,
+    changed: Query<(Entity, &GridViewer, &Transform), ChangedGridViewerFilter>,
+){
+    let mut dirty = HashSet::<u64>::default();
+
+    for entity in removed.read()
+    {
+        let Some((client_id, _)) = viewers.remove(entity) else { continue; };
+        dirty.insert(client_id);
+    }
+
+    for (entity, viewer, transform) in changed.iter()
+    {
+        let cell = grid.cell(transform.translation);
+        let current = (viewer.0, cell);
+        match viewers.insert(entity, viewer.0, cell)
+        {
+            // Only update attributes when crossing a cell boundary.
+            Some(prev) if prev == current => continue,
+            Some((prev_client, _)) => { dirty.insert(prev_client); }
+            None => (),
+        }
+        dirty.insert(viewer.0);
+    }
+
+    for client_id in dirty
+    {
+        sync_client_cells(&grid, &viewers, &mut attributes, client_id);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn resync_grid_viewers_on_reset(
+    event: Trigger<ClientAttributesReset>,
+    grid: Res<SpatialGrid>,
+    viewers: Res<GridViewers>,
+    mut attributes: ClientAttributes,
+){
+    sync_client_cells(&grid, &viewers, &mut attributes, event.client_id);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+type ChangedGridEntityFilter = (
+    With<Replicated>,
+    With<SpatialGridEntity>,
+    Or<(Changed<Transform>, Added<SpatialGridEntity>, Added<Replicated>)>,
+);
+
+fn update_grid_entities(
+    mut c: Commands,
+    grid: Res<SpatialGrid>,
+    changed: Query<(Entity, &Transform, Option<&GridCell>), ChangedGridEntityFilter>,
+){
+    for (entity, transform, current) in changed.iter()
+    {
+        // Only update conditions when crossing a cell boundary.
+        let cell = grid.cell(transform.translation);
+        if current.map(|c| c.0) == Some(cell) { continue; }
+        c.entity(entity).insert((GridCell(cell), VisibilityLayer::<SpatialGridEntity>::new(InCell(cell))));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+type GridEntityFilter = (With<Replicated>, With<SpatialGridEntity>);
+
+/// Recomputes all viewer and entity cells when the [`SpatialGrid`] changes.
+fn resync_spatial_grid(
+    mut c: Commands,
+    grid: Res<SpatialGrid>,
+    mut viewers: ResMut<GridViewers>,
+    mut attributes: ClientAttributes,
+    viewer_entities: Query<(Entity, &GridViewer, &Transform)>,
+    grid_entities: Query<(Entity, &Transform, Option<&GridCell>), GridEntityFilter>,
+){
+    if !valid_cell_size(&grid)
+    {
+        tracing::error!(?grid, "ignoring spatial grid change, the cell size must be positive");
+        return;
+    }
+
+    let mut dirty: HashSet<u64> = viewers.clients.keys().copied().collect();
+    *viewers = GridViewers::default();
+    for (entity, viewer, transform) in viewer_entities.iter()
+    {
+        viewers.insert(entity, viewer.0, grid.cell(transform.translation));
+        dirty.insert(viewer.0);
+    }
+    for client_id in dirty
+    {
+        sync_client_cells(&grid, &viewers, &mut attributes, client_id);
+    }
+
+    for (entity, transform, current) in grid_entities.iter()
+    {
+        let cell = grid.cell(transform.translation);
+        if current.map(|c| c.0) == Some(cell) { continue; }
+        c.entity(entity).insert((GridCell(cell), VisibilityLayer::<SpatialGridEntity>::new(InCell(cell))));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn remove_grid_entity(event: Trigger<OnRemove, SpatialGridEntity>, mut c: Commands)
+{
+    c.entity(event.target()).try_remove::<(GridCell, VisibilityLayer<SpatialGridEntity>)>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The dimensions of a [`SpatialGrid`].
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum GridDimensions
+{
+    /// Cells cover the XY plane. The Z coordinate of cells is always `0`.
+    #[default]
+    Xy,
+    /// Cells cover the XZ plane. The Y coordinate of cells is always `0`.
+    Xz,
+    /// Cells are cubes.
+    Xyz,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Settings for [`SpatialGridPlugin`].
+///
+/// The grid can be changed at runtime, in which case the cells of all viewers and entities are recomputed.
+#[derive(Resource, Debug, Copy, Clone, PartialEq)]
+pub struct SpatialGrid
+{
+    /// The width of cells.
+    ///
+    /// Must be positive. Runtime changes to an invalid cell size are ignored with an error.
+    pub cell_size: f32,
+    /// The number of cells visible in each direction from a viewer's cell.
+    ///
+    /// A radius of `1` in 2D gives viewers a 3x3 view of cells.
+    pub view_radius: u32,
+    /// See [`GridDimensions`].
+    pub dimensions: GridDimensions,
+}
+
+impl SpatialGrid
+{
+    /// Gets the cell containing a position.
+    pub fn cell(&self, position: Vec3) -> IVec3
+    {
+        let cell = (position / self.cell_size).floor().as_ivec3();
+        match self.dimensions
+        {
+            GridDimensions::Xy  => cell.with_z(0),
+            GridDimensions::Xz  => cell.with_y(0),
+            GridDimensions::Xyz => cell,
+        }
+    }
+
+    /// Iterates the cells visible from a cell.
+    pub fn cells_in_view(&self, cell: IVec3) -> impl Iterator<Item = InCell>
+    {
+        let r = self.view_radius as i32;
+        let (rx, ry, rz) = match self.dimensions
+        {
+            GridDimensions::Xy  => (r, r, 0),
+            GridDimensions::Xz  => (r, 0, r),
+            GridDimensions::Xyz => (r, r, r),
+        };
+        (-rx..=rx).flat_map(
+            move |x| (-ry..=ry).flat_map(
+                move |y| (-rz..=rz).map(move |z| InCell(cell + IVec3::new(x, y, z)))
+            )
+        )
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Attribute for grid cells added to clients by [`SpatialGridPlugin`].
+///
+/// Clients have the attribute for every cell in view of their [`GridViewer`] entities.
+///
+/// Cell coordinates are packed into 21 bits each, so cells are unique within `±2^20` cells of the origin.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct InCell(pub IVec3);
+
+impl VisibilityAttribute for InCell
+{
+    fn inner_attribute_id(&self) -> u64
+    {
+        pack_coordinate(self.0.x) | (pack_coordinate(self.0.y) << 21) | (pack_coordinate(self.0.z) << 42)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component for entities that determine which grid cells a client can see.
+///
+/// The client gets [`InCell`] attributes for all cells within [`SpatialGrid::view_radius`] of the entity's
+/// `Transform`. If a client has multiple viewers then it can see the cells of all of them.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct GridViewer(pub u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component for `Replicated` entities that are visible to clients that can see the entity's grid cell.
+///
+/// The entity's `VisibilityLayer<SpatialGridEntity>` is set to `vis!(InCell(cell))` from the entity's `Transform`,
+/// and is combined with the entity's other [`VisibilityLayers`](VisibilityLayer). The layer is removed when this
+/// component is removed.
+#[derive(Component, Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct SpatialGridEntity;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component with the current cell of a [`SpatialGridEntity`].
+///
+/// Inserted automatically.
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct GridCell(pub IVec3);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Plugin for grid-based area-of-interest visibility.
+///
+/// Entities with [`GridViewer`] give their client [`InCell`] attributes for nearby cells, and `Replicated` entities
+/// with [`SpatialGridEntity`] are given conditions for their own cells. Attributes and conditions are only updated
+/// when entities cross cell boundaries.
+///
+/// Positions are read from `Transform`, so entities should not have parents.
+///
+/// Must be added after [`VisibilityAttributesPlugin`]. Panics if the grid's cell size is not positive.
+///
+/// Example:
+/**
+```rust
+app.add_plugins(SpatialGridPlugin(SpatialGrid{
+    cell_size: 50.0,
+    view_radius: 2,
+    dimensions: GridDimensions::Xz,
+}));
+
+fn spawn_player(mut c: Commands, client_id: u64)
+{
+    c.spawn((Replicated, Player, Transform::default(), GridViewer(client_id), SpatialGridEntity));
+}
+```
+*/
+#[derive(Debug, Clone)]
+pub struct SpatialGridPlugin(pub SpatialGrid);
+
+impl Plugin for SpatialGridPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        if !valid_cell_size(&self.0)
+        { panic!("SpatialGrid::cell_size must be positive, found {}", self.0.cell_size); }
+
+        app.insert_resource(self.0)
+            .init_resource::<GridViewers>()
+            .register_visibility_layer::<SpatialGridEntity>()
+            .add_observer(resync_grid_viewers_on_reset)
+            .add_observer(remove_grid_entity)
+            .add_systems(PostUpdate,
+                (
+                    resync_spatial_grid
+                        .run_if(resource_changed::<SpatialGrid>),
+                    (update_grid_viewers, update_grid_entities)
+                        .run_if(has_valid_grid),
+                )
+                    .chain()
+                    .before(VisibilityLayerSet)
+            );
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
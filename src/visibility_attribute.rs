@@ -2,10 +2,11 @@
 use crate::*;
 
 //third-party shortcuts
+use siphasher::sip::SipHasher13;
 
 //standard shortcuts
 use std::any::TypeId;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -38,13 +39,70 @@ impl VisibilityAttributeId
     {
         self.1
     }
+
+    /// Checks if the attribute has type `T`.
+    pub fn is<T: VisibilityAttribute>(self) -> bool
+    {
+        self.0 == TypeId::of::<T>()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Hasher for inner attribute ids that produces the same output on all platforms.
+///
+/// Integers are written as little-endian bytes, and `usize`/`isize` are widened to 64 bits.
+struct StableHasher(SipHasher13);
+
+impl Hasher for StableHasher
+{
+    fn finish(&self) -> u64 { self.0.finish() }
+    fn write(&mut self, bytes: &[u8]) { self.0.write(bytes); }
+    fn write_u8(&mut self, i: u8) { self.0.write(&[i]); }
+    fn write_u16(&mut self, i: u16) { self.0.write(&i.to_le_bytes()); }
+    fn write_u32(&mut self, i: u32) { self.0.write(&i.to_le_bytes()); }
+    fn write_u64(&mut self, i: u64) { self.0.write(&i.to_le_bytes()); }
+    fn write_u128(&mut self, i: u128) { self.0.write(&i.to_le_bytes()); }
+    fn write_usize(&mut self, i: usize) { self.write_u64(i as u64); }
+    fn write_i8(&mut self, i: i8) { self.write_u8(i as u8); }
+    fn write_i16(&mut self, i: i16) { self.write_u16(i as u16); }
+    fn write_i32(&mut self, i: i32) { self.write_u32(i as u32); }
+    fn write_i64(&mut self, i: i64) { self.write_u64(i as u64); }
+    fn write_i128(&mut self, i: i128) { self.write_u128(i as u128); }
+    fn write_isize(&mut self, i: isize) { self.write_u64(i as i64 as u64); }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Hashes a value into an inner attribute id.
+///
+/// This is used by the `VisibilityAttribute` derive for attributes with fields, and can also be used in manual
+/// implementations of [`VisibilityAttribute::inner_attribute_id`].
+///
+/// Ids are stable: values are hashed with SipHash-1-3 using fixed zero keys, integers are hashed as little-endian
+/// bytes, and `usize`/`isize` are hashed as 64-bit integers. The same value produces the same id in all builds and on
+/// all platforms as long as its `Hash` implementation doesn't change, so ids can be persisted or sent over the network.
+/// Changing the hashing scheme is a breaking change.
+pub fn hash_visibility_attribute<T: Hash + ?Sized>(value: &T) -> u64
+{
+    let mut hasher = StableHasher(SipHasher13::new_with_keys(0, 0));
+    value.hash(&mut hasher);
+    hasher.finish()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
 /// Signifies that a type is a visibility attribute.
 ///
-/// The `VisibilityAttribute` derive macro will derive [`DefaultVisibilityAttribute`] on your type.
+/// The `VisibilityAttribute` derive macro supports:
+/// - **Zero-sized structs**: derives [`DefaultVisibilityAttribute`] on your type (requires `Default` and `PartialEq`).
+/// - **Structs with fields**: the inner id is a hash of the fields (see [`hash_visibility_attribute`]). All fields
+///   must implement `Hash`.
+/// - **Enums**: the inner id is a hash of the variant index and the variant's fields (if any).
+///
+/// Add `#[visibility_attribute(packed)]` to a struct or enum to pack fields exactly into the inner id instead of
+/// hashing them. Packed fields must be fixed-size integers or `bool`s, and together must fit in 64 bits. For enums,
+/// the variant index is packed into the highest bits, reducing the bits available for fields.
 ///
 /**
 **Examples**
@@ -53,6 +111,22 @@ With a derive:
 ```rust
 #[derive(VisibilityAttribute, Default, Eq, PartialEq)]
 struct InCastle;
+
+#[derive(VisibilityAttribute)]
+enum Team
+{
+    Red,
+    Blue,
+    Custom(String),
+}
+
+#[derive(VisibilityAttribute)]
+#[visibility_attribute(packed)]
+struct InLocation
+{
+    x: u32,
+    y: u32,
+}
 ```
 
 Manually implemented:
@@ -69,7 +143,8 @@ pub trait VisibilityAttribute: Sized + 'static
 {
     /// Returns the inner id of this attribute.
     ///
-    /// If your attribute contains non-type information (e.g. a client id), then you should manually implement this.
+    /// If your attribute contains non-type information (e.g. a client id), then you should manually implement this or
+    /// derive it on a struct with fields or an enum.
     ///
     /// Note that ids are domain-separated by attribute type, so you can safely use the full `u64` range to define your
     /// inner id.
@@ -88,7 +163,9 @@ impl<T: VisibilityAttribute> IntoVisibilityCondition for T
 {
     fn build(self, mut builder: VisibilityConditionBuilder) -> VisibilityConditionBuilder
     {
-        builder.push_attr_node(self.attribute_id());
+        let attribute_id = self.attribute_id();
+        record_attribute(&self, attribute_id);
+        builder.push_attr_node(attribute_id);
         builder
     }
 }
@@ -2,9 +2,11 @@
 use crate::*;
 
 //third-party shortcuts
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
-use bevy_replicon::prelude::{ClientVisibility, ServerSet, VisibilityPolicy};
+use bevy_replicon::prelude::{ClientVisibility, Replicated, ServerSet, VisibilityPolicy};
 use bevy_replicon::server::ServerPlugin;
+use bevy_replicon::server::server_tick::ServerTick;
 use bevy_replicon::shared::backend::connected_client::{NetworkId, NetworkIdMap};
 
 //standard shortcuts
@@ -19,15 +21,44 @@ struct NeedsVisibilityReset;
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Client entities are read by default attribute factories, and written to when updating visibility.
+#[derive(SystemParam)]
+struct ClientEntities<'w, 's>
+{
+    settings: Res<'w, VisibilitySettings>,
+    entities: ParamSet<'w, 's, (Query<'w, 's, EntityRef<'static>>, Query<'w, 's, &'static mut ClientVisibility>)>,
+}
+
+impl ClientEntities<'_, '_>
+{
+    /// Collects the default attributes of a client.
+    fn default_attributes(&mut self, client_entity: Entity, client_id: u64) -> Vec<VisibilityAttributeId>
+    {
+        let entities = self.entities.p0();
+        self.settings.collect_default_attributes(client_id, entities.get(client_entity).ok().as_ref())
+    }
+
+    /// Accesses client visibility.
+    fn visibility(&mut self) -> Query<'_, '_, &'static mut ClientVisibility>
+    {
+        self.entities.p1()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
 fn add_server_to_cache(server_id: u64) -> impl IntoSystem<(), (), ()>
 {
     IntoSystem::into_system(move
     |
+        settings: Res<VisibilitySettings>,
         mut visibility_cache: ResMut<VisibilityCache>,
         mut client_entities: Query<&mut ClientVisibility>
     |
     {
-        visibility_cache.add_server_as_client(&mut client_entities, server_id);
+        let default_attributes = settings.collect_default_attributes(server_id, None);
+        visibility_cache.add_server_as_client(&mut client_entities, server_id, &default_attributes);
     })
 }
 
@@ -41,7 +72,7 @@ fn reset_clients_on_disconnected(
 ){
     let client_entity = event.target();
     let Ok(client_id) = client_ids.get(client_entity) else { return };
-    visibility_cache.remove_client(client_id.get());
+    visibility_cache.disconnect_client(client_id.get());
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -53,7 +84,7 @@ fn reset_clients_on_start_replication(
     event: Trigger<OnAdd, ClientVisibility>,
     mut c: Commands,
     mut visibility_cache: ResMut<VisibilityCache>,
-    mut client_entities: Query<&mut ClientVisibility>,
+    mut client_entities: ClientEntities,
     client_ids: Query<&NetworkId>,
 ){
     let client_entity = event.target();
@@ -63,7 +94,9 @@ fn reset_clients_on_start_replication(
         return;
     };
     c.entity(client_entity).remove::<NeedsVisibilityReset>();
-    visibility_cache.reset_client(&mut client_entities, Some(client_entity), client_id.get());
+    let default_attributes = client_entities.default_attributes(client_entity, client_id.get());
+    visibility_cache.reset_client(&mut client_entities.visibility(), Some(client_entity), client_id.get(), &default_attributes);
+    c.trigger_targets(ClientAttributesReset{ client_id: client_id.get() }, client_entity);
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -73,13 +106,15 @@ fn fallback_reset_clients_on_network_id(
     event: Trigger<OnAdd, NetworkId>,
     mut c: Commands,
     mut visibility_cache: ResMut<VisibilityCache>,
-    mut client_entities: Query<&mut ClientVisibility>,
+    mut client_entities: ClientEntities,
     client_ids: Query<&NetworkId, With<NeedsVisibilityReset>>,
 ){
     let client_entity = event.target();
     let Ok(client_id) = client_ids.get(client_entity) else { return };
     c.entity(client_entity).remove::<NeedsVisibilityReset>();
-    visibility_cache.reset_client(&mut client_entities, Some(client_entity), client_id.get());
+    let default_attributes = client_entities.default_attributes(client_entity, client_id.get());
+    visibility_cache.reset_client(&mut client_entities.visibility(), Some(client_entity), client_id.get(), &default_attributes);
+    c.trigger_targets(ClientAttributesReset{ client_id: client_id.get() }, client_entity);
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -88,13 +123,75 @@ fn fallback_reset_clients_on_network_id(
 fn repair_clients(
     event: Trigger<OnAdd, ClientVisibility>,
     mut visibility_cache: ResMut<VisibilityCache>,
-    mut client_entities: Query<&mut ClientVisibility>,
+    mut client_entities: ClientEntities,
     client_ids: Query<&NetworkId>,
 ){
     let client_entity = event.target();
     let Ok(client_id) = client_ids.get(client_entity) else { return };
     // This will load visibility settings into replicon, which clears visibility when a client disconnects.
-    visibility_cache.repair_client(&mut client_entities, Some(client_entity), client_id.get());
+    let default_attributes = client_entities.default_attributes(client_entity, client_id.get());
+    visibility_cache.repair_client(&mut client_entities.visibility(), Some(client_entity), client_id.get(), &default_attributes);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+type MissingConditionFilter = (
+    Added<Replicated>,
+    Without<VisibilityCondition>,
+    Without<VisibleWith>,
+    Without<InheritVisibility>,
+);
+
+/// Runs after all conditions inserted this tick by the crate's subsystems are applied.
+///
+/// Entities linked to other entities' visibility don't need their own conditions.
+fn handle_missing_conditions(
+    mut c: Commands,
+    settings: Res<VisibilitySettings>,
+    missing: Query<Entity, MissingConditionFilter>,
+){
+    if settings.default_condition().is_none() && !settings.warns_missing_condition() { return; }
+
+    for entity in missing.iter()
+    {
+        if settings.warns_missing_condition()
+        { tracing::warn!(?entity, "replicated entity spawned without a visibility condition"); }
+
+        let Some(default_condition) = settings.default_condition() else { continue; };
+        c.entity(entity).insert(default_condition.clone());
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn remove_entity_output(event: Trigger<OnRemove, Replicated>, mut visibility_cache: ResMut<VisibilityCache>)
+{
+    visibility_cache.remove_entity_output(event.target());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn apply_visibility_settings(settings: Res<VisibilitySettings>, mut visibility_cache: ResMut<VisibilityCache>)
+{
+    visibility_cache.set_default_linger(settings.linger());
+    visibility_cache.set_budget(settings.budget());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Hides lingering entities whose linger has expired, then admits pending reveals.
+fn update_visibility_output(
+    time: Res<Time>,
+    server_tick: Res<ServerTick>,
+    id_map: Res<NetworkIdMap>,
+    mut visibility_cache: ResMut<VisibilityCache>,
+    mut client_entities: Query<&mut ClientVisibility>,
+){
+    visibility_cache.update_output(&id_map, &mut client_entities, time.elapsed(), **server_tick);
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -123,7 +220,7 @@ fn handle_visibility_changes(
 ){
     for (entity, visibility) in changed.iter()
     {
-        visibility_cache.add_entity_condition(&id_map, &mut client_entities, entity, &*visibility);
+        visibility_cache.add_entity_condition(&id_map, &mut client_entities, entity, visibility);
     }
 }
 
@@ -158,6 +255,18 @@ impl Plugin for AttributesRepairPlugin
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Entity event triggered on a client entity after the client's attributes were reset.
+///
+/// Clients are reset when they start replicating if [`ReconnectPolicy::Reset`] is used. Observe this event if you
+/// need to re-add attributes to clients after a reset.
+#[derive(Event, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ClientAttributesReset
+{
+    pub client_id: u64,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// System set that collects entity [`VisibilityCondition`] changes and translates them into `bevy_replicon` client
 /// visibility.
 ///
@@ -173,20 +282,29 @@ pub enum ReconnectPolicy
     ///
     /// Only attributes added while the client is replicating will be used to determine visibility.
     ///
-    /// Newly-connected clients always start with the builtin [`Global`] and [`Client`] attributes.
+    /// Newly-connected clients always start with their default attributes (see
+    /// [`VisibilitySettings::with_default_attribute`]).
     Reset,
     /// Preserves client attributes after a disconnect, and repairs client visibility within `bevy_replicon` when
     /// the client reconnects and starts replicating again.
     ///
     /// Attributes can be added to clients at any time, even before they connect for the first time.
     ///
-    /// Newly-connected clients always start with the builtin [`Global`] and [`Client`] attributes.
+    /// Clients are given their default attributes (see [`VisibilitySettings::with_default_attribute`]) each time
+    /// they start replicating.
+    ///
+    /// Visibility overrides (see [`ClientAttributes::force_visible`]) are also kept after a disconnect. Use
+    /// [`ClientAttributes::clear_overrides`] to release them for clients that won't reconnect.
     Repair,
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
 /// Plugin that sets up visibility handling systems in a server using `bevy_replicon`.
+///
+/// Optional settings are configured with the [`VisibilitySettings`] resource. The plugin initializes default settings,
+/// and settings inserted before or after adding the plugin are applied when the app updates.
+#[derive(Debug, Clone)]
 pub struct VisibilityAttributesPlugin
 {
     /// Records the server's client id if it is a player.
@@ -204,7 +322,7 @@ impl Plugin for VisibilityAttributesPlugin
         // todo: replace with plugin dependencies if bevy adds them ??
         let added_plugins = app.get_added_plugins::<ServerPlugin>();
         let server_plugin = added_plugins
-            .get(0)
+            .first()
             .expect("bevy_replicon server plugins are required for VisibilityAttributesPlugin");
         if let VisibilityPolicy::Blacklist = server_plugin.visibility_policy
         {
@@ -213,14 +331,48 @@ impl Plugin for VisibilityAttributesPlugin
         }
 
         app.insert_resource(VisibilityCache::new())
+            .init_resource::<VisibilitySettings>()
+            .init_resource::<VisibilityPredicates>()
+            .init_resource::<VisibilityPresets>()
+            .add_observer(remove_entity_output)
+            .add_observer(insert_visibility_linger)
+            .add_observer(replace_visibility_linger)
+            .add_observer(insert_visibility_priority)
+            .add_observer(replace_visibility_priority)
+            .add_observer(insert_visibility_preset)
+            .add_observer(replace_visibility_preset)
+            .add_observer(remove_visibility_preset)
+            .add_observer(insert_inherit_visibility)
+            .add_observer(remove_inherit_visibility)
             .configure_sets(PostUpdate, VisibilityUpdateSet.before(ServerSet::Send))
+            .register_visibility_layer::<VisibilityPreset>()
+            .register_visibility_layer::<InheritVisibility>()
+            .add_systems(PostUpdate, update_visibility_presets.before(VisibilityLayerSet))
             .add_systems(PostUpdate,
                 (
-                    // handle removals first in case of removal -> insertion in different systems
-                    handle_visibility_removals,
-                    handle_visibility_changes,
+                    (
+                        // detect missing conditions after conditions inserted by layers are applied
+                        handle_missing_conditions,
+                        // handle removals first in case of removal -> insertion in different systems
+                        handle_visibility_removals,
+                        handle_visibility_changes,
+                        update_visibility_links,
+                        // evaluate predicates after conditions are updated so new entities are evaluated this tick
+                        evaluate_visibility_predicates
+                            .run_if(has_visibility_predicates),
+                        // update the output after visibility changes so entities that become visible again this tick
+                        // are not hidden
+                        update_visibility_output
+                            .after(bevy_replicon::server::increment_tick),
+                    )
+                        .chain(),
+                    apply_scheduled_attributes
+                        .after(bevy_replicon::server::increment_tick)
+                        .before(handle_visibility_removals),
+                    apply_visibility_settings
+                        .run_if(resource_changed::<VisibilitySettings>)
+                        .before(handle_visibility_removals),
                 )
-                    .chain()
                     .in_set(VisibilityUpdateSet)
             );
 
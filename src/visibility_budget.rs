@@ -0,0 +1,83 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn insert_visibility_priority(
+    event: Trigger<OnInsert, VisibilityPriority>,
+    mut visibility_cache: ResMut<VisibilityCache>,
+    priorities: Query<&VisibilityPriority>,
+){
+    let entity = event.target();
+    let Ok(priority) = priorities.get(entity) else { return; };
+    visibility_cache.set_entity_priority(entity, Some(priority.0));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn replace_visibility_priority(
+    event: Trigger<OnReplace, VisibilityPriority>,
+    mut visibility_cache: ResMut<VisibilityCache>,
+){
+    visibility_cache.set_entity_priority(event.target(), None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Limits how quickly entities are revealed to clients.
+///
+/// When an entity becomes visible to a client, the reveal is queued. Queued reveals are admitted once per server tick
+/// in [`VisibilityPriority`] order (highest first), up to the limits of the budget. This spreads out large bursts of
+/// newly-visible entities (e.g. when a client teleports into a crowded area) across multiple ticks.
+///
+/// Entities forced visible with [`ClientAttributes::force_visible`] are revealed immediately and bypass the budget,
+/// but still count toward [`Self::max_visible`].
+///
+/// Set with [`VisibilitySettings::with_budget`]. The budget is disabled by default.
+///
+/// Example:
+/**
+```rust
+app.insert_resource(
+    VisibilitySettings::default()
+        .with_budget(VisibilityBudget{ max_reveals_per_tick: Some(100), max_visible: Some(2000) })
+);
+```
+*/
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct VisibilityBudget
+{
+    /// The maximum number of entities that can be revealed to a client each tick.
+    pub max_reveals_per_tick: Option<usize>,
+    /// The maximum number of entities that can be visible to a client.
+    ///
+    /// Reveals are queued until the client has room for more visible entities.
+    pub max_visible: Option<usize>,
+}
+
+impl VisibilityBudget
+{
+    /// Returns `true` if the budget limits reveals.
+    pub fn is_active(&self) -> bool
+    {
+        self.max_reveals_per_tick.is_some() || self.max_visible.is_some()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component that sets the priority of revealing an entity to clients when there is a [`VisibilityBudget`].
+///
+/// Entities with higher priorities are revealed first. Entities without this component have priority `0`.
+#[derive(Component, Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct VisibilityPriority(pub i32);
+
+//-------------------------------------------------------------------------------------------------------------------
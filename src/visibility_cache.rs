@@ -6,10 +6,13 @@ use bevy::prelude::*;
 use bevy::ecs::entity::{EntityHashMap, EntityHashSet};
 use bevy_replicon::prelude::ClientVisibility;
 use bevy_replicon::shared::backend::connected_client::{NetworkId, NetworkIdMap};
+use bevy_replicon::shared::replicon_tick::RepliconTick;
 
 //standard shortcuts
+use std::any::TypeId;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
@@ -23,6 +26,706 @@ enum UpdateType
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Link from an entity to the entity whose visibility it follows.
+///
+/// See [`VisibleWith`] and [`InheritVisibility`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct VisibilityLink
+{
+    /// The entity whose visibility is followed.
+    ///
+    /// Linked entities without an anchor are hidden.
+    pub(crate) anchor: Option<Entity>,
+    /// If `true`, then the linked entity's own condition must also be satisfied.
+    pub(crate) and_own: bool,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks entities whose visibility follows the output visibility of other entities.
+#[derive(Default)]
+struct VisibilityLinks
+{
+    /// [ entity : link ]
+    links: EntityHashMap<VisibilityLink>,
+    /// [ anchor : [ linked entity ] ]
+    dependents: EntityHashMap<EntityHashSet>,
+    /// [ linked entity : [ client that satisfies the entity's own condition ] ]
+    own_visible: EntityHashMap<HashSet<u64>>,
+}
+
+impl VisibilityLinks
+{
+    fn is_linked(&self, entity: Entity) -> bool
+    {
+        !self.links.is_empty() && self.links.contains_key(&entity)
+    }
+
+    /// Sets or removes an entity's link.
+    ///
+    /// Returns `false` if the link didn't change.
+    fn set_link(&mut self, entity: Entity, link: Option<VisibilityLink>) -> bool
+    {
+        let prev = match link
+        {
+            Some(link) => self.links.insert(entity, link),
+            None       => self.links.remove(&entity),
+        };
+        if prev == link { return false; }
+
+        if let Some(anchor) = prev.and_then(|l| l.anchor)
+        {
+            if let Some(dependents) = self.dependents.get_mut(&anchor)
+            {
+                dependents.remove(&entity);
+                if dependents.is_empty() { self.dependents.remove(&anchor); }
+            }
+        }
+        if let Some(anchor) = link.and_then(|l| l.anchor)
+        {
+            self.dependents.entry(anchor).or_default().insert(entity);
+        }
+        if link.is_none() { self.own_visible.remove(&entity); }
+
+        true
+    }
+
+    /// Records whether a client satisfies a linked entity's own condition.
+    fn set_own_visibility(&mut self, client_id: u64, entity: Entity, visibility: bool)
+    {
+        match visibility
+        {
+            true  => { self.own_visible.entry(entity).or_default().insert(client_id); }
+            false =>
+            {
+                let Some(clients) = self.own_visible.get_mut(&entity) else { return; };
+                clients.remove(&client_id);
+                if clients.is_empty() { self.own_visible.remove(&entity); }
+            }
+        }
+    }
+
+    /// Gets the visibility of a linked entity for a client.
+    ///
+    /// Overrides take precedence over the anchor's visibility.
+    fn visibility(
+        &self,
+        overrides: &HashMap<u64, EntityHashMap<bool>>,
+        client_visibility: &ClientVisibility,
+        client_id: u64,
+        entity: Entity,
+    ) -> bool
+    {
+        if let Some(forced) = overrides.get(&client_id).and_then(|o| o.get(&entity)) { return *forced; }
+        let Some(link) = self.links.get(&entity) else { return false; };
+        let Some(anchor) = link.anchor else { return false; };
+        if !client_visibility.is_visible(anchor) { return false; }
+        !link.and_own || self.own_visible.get(&entity).is_some_and(|clients| clients.contains(&client_id))
+    }
+
+    fn remove_client(&mut self, client_id: u64)
+    {
+        if self.own_visible.is_empty() { return; }
+        self.own_visible.retain(
+            |_, clients|
+            {
+                clients.remove(&client_id);
+                !clients.is_empty()
+            }
+        );
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Applies entity visibility to `bevy_replicon`.
+///
+/// Visibility is applied to linked entities whenever it is applied to their anchors. The entities visible to each
+/// client are only tracked if a [`VisibilityBudget`] limits the number of visible entities.
+#[derive(Default)]
+struct VisibleEntities
+{
+    enabled: bool,
+    /// [ client : [ entity ] ]
+    clients: HashMap<u64, EntityHashSet>,
+    links: VisibilityLinks,
+}
+
+impl VisibleEntities
+{
+    /// Sets the visibility of an entity for a client, then updates entities linked to it.
+    fn set(
+        &mut self,
+        overrides: &HashMap<u64, EntityHashMap<bool>>,
+        client_visibility: &mut ClientVisibility,
+        client_id: u64,
+        entity: Entity,
+        visibility: bool,
+    ){
+        client_visibility.set_visibility(entity, visibility);
+        self.track(client_id, entity, visibility);
+
+        // Update linked entities.
+        // - Entities that already have the right visibility are skipped, which also stops link cycles.
+        let Some(dependents) = self.links.dependents.get(&entity) else { return; };
+        let dependents: Vec<Entity> = dependents.iter().copied().collect();
+        for dependent in dependents
+        {
+            let visibility = self.links.visibility(overrides, client_visibility, client_id, dependent);
+            if client_visibility.is_visible(dependent) == visibility { continue; }
+            self.set(overrides, client_visibility, client_id, dependent, visibility);
+        }
+    }
+
+    /// Tracks the visibility of an entity for a client.
+    fn track(&mut self, client_id: u64, entity: Entity, visibility: bool)
+    {
+        if !self.enabled { return; }
+
+        match visibility
+        {
+            true => { self.clients.entry(client_id).or_default().insert(entity); }
+            false =>
+            {
+                let Some(visible) = self.clients.get_mut(&client_id) else { return; };
+                visible.remove(&entity);
+                if visible.is_empty() { self.clients.remove(&client_id); }
+            }
+        }
+    }
+
+    /// Counts the entities visible to a client.
+    fn count(&self, client_id: u64) -> usize
+    {
+        self.clients.get(&client_id).map(|v| v.len()).unwrap_or_default()
+    }
+
+    fn remove_entity(&mut self, entity: Entity)
+    {
+        if !self.enabled { return; }
+        self.clients.retain(
+            |_, visible|
+            {
+                visible.remove(&entity);
+                !visible.is_empty()
+            }
+        );
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Predicates and parameters of a condition that is evaluated per entity.
+#[derive(Default)]
+struct EntityConditionTerms
+{
+    predicates: Vec<VisibilityPredicateId>,
+    params: Vec<VisibilityParamId>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks per-entity visibility of conditions that contain predicates or parameters.
+///
+/// Conditions with predicates or parameters can't be evaluated once per client, so each of their entities is
+/// evaluated separately.
+#[derive(Default)]
+struct PredicateVisibility
+{
+    /// [ condition id : terms ]
+    conditions: HashMap<VisibilityConditionId, EntityConditionTerms>,
+    /// [ client : [ entity : [ predicate : result ] ] ]
+    results: HashMap<u64, EntityHashMap<HashMap<VisibilityPredicateId, bool>>>,
+    /// [ entity : [ client ] ]
+    visible: EntityHashMap<HashSet<u64>>,
+    /// [ entity : [ param : bound condition ] ]
+    bindings: EntityHashMap<HashMap<VisibilityParamId, VisibilityCondition>>,
+    /// [ attribute : [ entity ] ]
+    ///
+    /// Entities whose bindings contain each attribute.
+    bound_attributes: HashMap<VisibilityAttributeId, EntityHashSet>,
+    /// Set when clients or entities may be missing predicate results.
+    unevaluated: bool,
+}
+
+impl PredicateVisibility
+{
+    /// Returns `true` if a condition contains predicates or parameters.
+    fn has(&self, condition_id: &VisibilityConditionId) -> bool
+    {
+        !self.conditions.is_empty() && self.conditions.contains_key(condition_id)
+    }
+
+    /// Returns `true` if a condition contains a parameter.
+    fn has_param(&self, condition_id: &VisibilityConditionId, param: VisibilityParamId) -> bool
+    {
+        self.conditions.get(condition_id).is_some_and(|terms| terms.params.contains(&param))
+    }
+
+    fn add_condition(&mut self, condition_id: VisibilityConditionId, condition: &VisibilityCondition)
+    {
+        let mut terms = EntityConditionTerms::default();
+        for predicate in condition.iter_predicates()
+        {
+            if terms.predicates.contains(&predicate) { continue; }
+            terms.predicates.push(predicate);
+        }
+        for param in condition.iter_params()
+        {
+            if terms.params.contains(&param) { continue; }
+            terms.params.push(param);
+        }
+        if terms.predicates.is_empty() && terms.params.is_empty() { return; }
+        self.conditions.insert(condition_id, terms);
+    }
+
+    fn remove_condition(&mut self, condition_id: &VisibilityConditionId)
+    {
+        self.conditions.remove(condition_id);
+    }
+
+    /// Iterates the predicates used by an entity with a condition, including predicates in its bound parameters.
+    ///
+    /// Predicates may repeat.
+    fn iter_entity_predicates(
+        &self,
+        condition_id: &VisibilityConditionId,
+        entity: Entity,
+    ) -> impl Iterator<Item = VisibilityPredicateId> + '_
+    {
+        let terms = self.conditions.get(condition_id);
+        let bindings = self.bindings.get(&entity);
+        let bound = terms
+            .into_iter()
+            .flat_map(|terms| terms.params.iter())
+            .filter_map(move |param| bindings.and_then(|b| b.get(param)))
+            .flat_map(|bound| bound.iter_predicates());
+        terms
+            .into_iter()
+            .flat_map(|terms| terms.predicates.iter().copied())
+            .chain(bound)
+    }
+
+    /// Sets or removes an entity's parameter binding.
+    ///
+    /// Returns `false` if the binding didn't change.
+    fn set_binding(&mut self, entity: Entity, param: VisibilityParamId, binding: Option<VisibilityCondition>) -> bool
+    {
+        let bindings = self.bindings.entry(entity).or_default();
+        if bindings.get(&param) == binding.as_ref()
+        {
+            if bindings.is_empty() { self.bindings.remove(&entity); }
+            return false;
+        }
+
+        // Unindex the entity's previous bound attributes.
+        for attribute in bindings.values().flat_map(|bound| bound.iter_attributes())
+        {
+            let Some(entities) = self.bound_attributes.get_mut(&attribute) else { continue; };
+            entities.remove(&entity);
+            if entities.is_empty() { self.bound_attributes.remove(&attribute); }
+        }
+
+        // Update the binding.
+        match binding
+        {
+            Some(binding) => { bindings.insert(param, binding); }
+            None          => { bindings.remove(&param); }
+        }
+
+        // Index the entity's bound attributes.
+        for attribute in bindings.values().flat_map(|bound| bound.iter_attributes())
+        {
+            self.bound_attributes.entry(attribute).or_default().insert(entity);
+        }
+        if bindings.is_empty() { self.bindings.remove(&entity); }
+
+        true
+    }
+
+    /// Collects entities whose bindings contain an attribute.
+    fn extend_bound_entities(&self, attribute: &VisibilityAttributeId, bound: &mut Vec<Entity>)
+    {
+        let Some(entities) = self.bound_attributes.get(attribute) else { return; };
+        bound.extend(entities.iter().copied());
+    }
+
+    /// Evaluates a condition for a client and entity.
+    fn evaluate(
+        &self,
+        condition: &VisibilityCondition,
+        attributes: &HashSet<VisibilityAttributeId>,
+        client_id: u64,
+        entity: Entity,
+    ) -> bool
+    {
+        let results = self.results.get(&client_id).and_then(|r| r.get(&entity));
+        let bindings = self.bindings.get(&entity);
+        condition.evaluate_with_params(
+            |a| attributes.contains(&a),
+            |p| results.and_then(|r| r.get(&p)).copied().unwrap_or_default(),
+            |p| bindings.and_then(|b| b.get(&p)),
+        )
+    }
+
+    /// Returns `true` if a client can see an entity according to the entity's condition.
+    fn is_visible(&self, client_id: u64, entity: Entity) -> bool
+    {
+        self.visible.get(&entity).is_some_and(|clients| clients.contains(&client_id))
+    }
+
+    /// Evaluates a condition for each of its entities and sets their visibility for a client.
+    fn update_entities(
+        &mut self,
+        output: &mut VisibilityOutput,
+        client_visibility: &mut ClientVisibility,
+        client_id: u64,
+        condition: &VisibilityCondition,
+        entities: &EntityHashSet,
+        attributes: &HashSet<VisibilityAttributeId>,
+    ){
+        for entity in entities.iter()
+        {
+            self.update_entity(output, client_visibility, client_id, condition, *entity, attributes);
+        }
+    }
+
+    /// Evaluates entities with bound parameters for a client and sets their visibility.
+    ///
+    /// Entities whose conditions don't use parameters are ignored.
+    ///
+    /// `entity_condition` looks up the id and condition of each entity.
+    fn update_bound_entities<'a>(
+        &mut self,
+        output: &mut VisibilityOutput,
+        client_visibility: &mut ClientVisibility,
+        client_id: u64,
+        bound: &[Entity],
+        entity_condition: impl Fn(Entity) -> Option<(VisibilityConditionId, &'a VisibilityCondition)>,
+        attributes: &HashSet<VisibilityAttributeId>,
+    ){
+        for entity in bound.iter()
+        {
+            let Some((condition_id, condition)) = entity_condition(*entity) else { continue; };
+            if !self.has(&condition_id) { continue; }
+            self.update_entity(output, client_visibility, client_id, condition, *entity, attributes);
+        }
+    }
+
+    /// Evaluates a condition for an entity and sets its visibility for a client.
+    ///
+    /// Returns the entity's visibility.
+    fn update_entity(
+        &mut self,
+        output: &mut VisibilityOutput,
+        client_visibility: &mut ClientVisibility,
+        client_id: u64,
+        condition: &VisibilityCondition,
+        entity: Entity,
+        attributes: &HashSet<VisibilityAttributeId>,
+    ) -> bool
+    {
+        let visibility = self.evaluate(condition, attributes, client_id, entity);
+        match visibility
+        {
+            true  => { self.visible.entry(entity).or_default().insert(client_id); }
+            false =>
+            {
+                if let Some(clients) = self.visible.get_mut(&entity)
+                {
+                    clients.remove(&client_id);
+                    if clients.is_empty() { self.visible.remove(&entity); }
+                }
+            }
+        }
+        tracing::trace!(?client_id, ?entity, ?condition, "predicate visibility <{visibility}>");
+        output.set_visibility(client_visibility, client_id, entity, visibility);
+        visibility
+    }
+
+    /// Drops an entity's predicate results for predicates that its condition and bindings don't use.
+    fn retain_entity_results(&mut self, entity: Entity, condition_id: VisibilityConditionId)
+    {
+        if self.results.is_empty() { return; }
+        let predicates: Vec<_> = self.iter_entity_predicates(&condition_id, entity).collect();
+        self.results.retain(
+            |_, results|
+            {
+                let Some(entity_results) = results.get_mut(&entity) else { return true; };
+                entity_results.retain(|predicate, _| predicates.contains(predicate));
+                if entity_results.is_empty() { results.remove(&entity); }
+                !results.is_empty()
+            }
+        );
+    }
+
+    /// Removes an entity's visibility, and its predicate results if `despawned`.
+    ///
+    /// Returns the clients that could see the entity.
+    fn remove_entity(&mut self, entity: Entity, despawned: bool) -> HashSet<u64>
+    {
+        if despawned && !self.results.is_empty()
+        {
+            self.results.retain(
+                |_, results|
+                {
+                    results.remove(&entity);
+                    !results.is_empty()
+                }
+            );
+        }
+        self.visible.remove(&entity).unwrap_or_default()
+    }
+
+    fn remove_client(&mut self, client_id: u64)
+    {
+        self.results.remove(&client_id);
+        if self.visible.is_empty() { return; }
+        self.visible.retain(
+            |_, clients|
+            {
+                clients.remove(&client_id);
+                !clients.is_empty()
+            }
+        );
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Final stage of visibility updates.
+///
+/// All entity visibility changes sent to `bevy_replicon` pass through here.
+#[derive(Default)]
+struct VisibilityOutput
+{
+    /// [ client : [ entity : forced visibility ] ]
+    overrides: HashMap<u64, EntityHashMap<bool>>,
+
+    /// Linger applied to entities without their own linger.
+    default_linger: Option<VisibilityLinger>,
+    /// [ entity : linger ]
+    entity_linger: EntityHashMap<VisibilityLinger>,
+    /// [ client : [ entity : when the entity will be hidden ] ]
+    lingering: HashMap<u64, EntityHashMap<ScheduledAt>>,
+
+    /// Limits on revealing entities to clients.
+    budget: VisibilityBudget,
+    /// [ entity : priority ]
+    entity_priority: EntityHashMap<i32>,
+    /// [ client : [ entity ] ]
+    pending_reveals: HashMap<u64, EntityHashSet>,
+    /// Tick when pending reveals were last admitted.
+    last_admission: Option<RepliconTick>,
+    /// Entities visible to each client.
+    visible: VisibleEntities,
+}
+
+impl VisibilityOutput
+{
+    /// Gets the forced visibility of an entity for a client.
+    fn get_override(&self, client_id: u64, entity: Entity) -> Option<bool>
+    {
+        if self.overrides.is_empty() { return None; }
+        self.overrides.get(&client_id).and_then(|o| o.get(&entity)).copied()
+    }
+
+    /// Gets the linger of an entity.
+    fn get_linger(&self, entity: Entity) -> Option<VisibilityLinger>
+    {
+        self.entity_linger.get(&entity).copied().or(self.default_linger)
+    }
+
+    /// Cancels a pending hide of an entity for a client.
+    fn cancel_linger(&mut self, client_id: u64, entity: Entity)
+    {
+        if self.lingering.is_empty() { return; }
+        let Some(lingering) = self.lingering.get_mut(&client_id) else { return; };
+        if lingering.remove(&entity).is_none() { return; }
+        if lingering.is_empty() { self.lingering.remove(&client_id); }
+        tracing::trace!(?client_id, ?entity, "cancelled lingering visibility");
+    }
+
+    /// Cancels a pending reveal of an entity for a client.
+    fn cancel_reveal(&mut self, client_id: u64, entity: Entity)
+    {
+        if self.pending_reveals.is_empty() { return; }
+        let Some(pending) = self.pending_reveals.get_mut(&client_id) else { return; };
+        if !pending.remove(&entity) { return; }
+        if pending.is_empty() { self.pending_reveals.remove(&client_id); }
+        tracing::trace!(?client_id, ?entity, "cancelled pending reveal");
+    }
+
+    /// Sets the visibility of an entity for a client.
+    ///
+    /// Overrides take precedence over `visibility`. If the entity has a linger then hiding it is deferred. If there is
+    /// a [`VisibilityBudget`] then revealing it is deferred.
+    ///
+    /// Linked entities follow their anchor's visibility instead, and `visibility` is only used if they are linked to
+    /// their own condition.
+    fn set_visibility(&mut self, client_visibility: &mut ClientVisibility, client_id: u64, entity: Entity, visibility: bool)
+    {
+        if self.visible.links.is_linked(entity)
+        {
+            self.visible.links.set_own_visibility(client_id, entity, visibility);
+            let visibility = self.visible.links.visibility(&self.overrides, client_visibility, client_id, entity);
+            self.visible.set(&self.overrides, client_visibility, client_id, entity, visibility);
+            return;
+        }
+
+        let forced = self.get_override(client_id, entity);
+        let visibility = forced.unwrap_or(visibility);
+
+        match visibility
+        {
+            true =>
+            {
+                self.cancel_linger(client_id, entity);
+
+                // Defer revealing hidden entities if there is a budget.
+                // - Overrides are applied immediately.
+                if forced.is_none() && self.budget.is_active() && !client_visibility.is_visible(entity)
+                {
+                    self.pending_reveals.entry(client_id).or_default().insert(entity);
+                    tracing::trace!(?client_id, ?entity, "pending reveal");
+                    return;
+                }
+
+                self.cancel_reveal(client_id, entity);
+            }
+            false =>
+            {
+                self.cancel_reveal(client_id, entity);
+
+                // Defer hiding visible entities if they linger.
+                // - Overrides are applied immediately.
+                if forced.is_none() && client_visibility.is_visible(entity)
+                {
+                    if let Some(linger) = self.get_linger(entity)
+                    {
+                        let hide_at = linger.deadline();
+                        self.lingering.entry(client_id).or_default().entry(entity).or_insert(hide_at);
+                        tracing::trace!(?client_id, ?entity, ?hide_at, "lingering visibility");
+                        return;
+                    }
+                }
+
+                self.cancel_linger(client_id, entity);
+            }
+        }
+
+        self.visible.set(&self.overrides, client_visibility, client_id, entity, visibility);
+    }
+
+    /// Hides lingering entities whose linger has expired, then admits pending reveals.
+    fn update(
+        &mut self,
+        id_map: &NetworkIdMap,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        elapsed: Duration,
+        tick: RepliconTick,
+    ){
+        self.update_lingering(id_map, client_entities, elapsed, tick);
+
+        // Admit reveals once per tick.
+        if self.last_admission == Some(tick) { return; }
+        self.last_admission = Some(tick);
+        self.admit_pending_reveals(id_map, client_entities);
+    }
+
+    /// Hides lingering entities whose linger has expired.
+    fn update_lingering(
+        &mut self,
+        id_map: &NetworkIdMap,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        elapsed: Duration,
+        tick: RepliconTick,
+    ){
+        let overrides = &self.overrides;
+        let visible = &mut self.visible;
+        self.lingering.retain(
+            |client_id, lingering|
+            {
+                // Discard entries for disconnected clients.
+                let Some(mut client_visibility) = id_map
+                    .get(&NetworkId::new(*client_id))
+                    .and_then(|e| client_entities.get_mut(*e).ok())
+                else { return false; };
+
+                lingering.retain(
+                    |entity, hide_at|
+                    {
+                        hide_at.resolve(elapsed, tick);
+                        if !hide_at.is_due(elapsed, tick) { return true; }
+                        tracing::trace!(?client_id, ?entity, "lingering visibility expired");
+                        visible.set(overrides, &mut client_visibility, *client_id, *entity, false);
+                        false
+                    }
+                );
+                !lingering.is_empty()
+            }
+        );
+    }
+
+    /// Reveals pending entities to clients in priority order, up to the budget.
+    fn admit_pending_reveals(&mut self, id_map: &NetworkIdMap, client_entities: &mut Query<&mut ClientVisibility>)
+    {
+        if self.pending_reveals.is_empty() { return; }
+
+        let budget = self.budget;
+        let entity_priority = &self.entity_priority;
+        let overrides = &self.overrides;
+        let visible = &mut self.visible;
+        let mut admitted = Vec::default();
+        self.pending_reveals.retain(
+            |client_id, pending|
+            {
+                // Discard entries for disconnected clients.
+                let Some(mut client_visibility) = id_map
+                    .get(&NetworkId::new(*client_id))
+                    .and_then(|e| client_entities.get_mut(*e).ok())
+                else { return false; };
+
+                let mut allowed = budget.max_reveals_per_tick.unwrap_or(usize::MAX);
+                if let Some(max_visible) = budget.max_visible
+                {
+                    allowed = allowed.min(max_visible.saturating_sub(visible.count(*client_id)));
+                }
+                if allowed == 0 { return true; }
+
+                // Sort by priority, with entity ids as a tie-breaker for determinism.
+                admitted.clear();
+                admitted.extend(pending.iter().copied());
+                admitted.sort_unstable_by_key(
+                    |e| (std::cmp::Reverse(entity_priority.get(e).copied().unwrap_or_default()), *e)
+                );
+                admitted.truncate(allowed);
+
+                for entity in admitted.iter()
+                {
+                    pending.remove(entity);
+                    visible.set(overrides, &mut client_visibility, *client_id, *entity, true);
+                }
+                tracing::trace!(?client_id, ?admitted, "admitted pending reveals");
+
+                !pending.is_empty()
+            }
+        );
+    }
+
+    /// Removes a client's output state except overrides.
+    fn reset_client(&mut self, client_id: u64)
+    {
+        self.lingering.remove(&client_id);
+        self.pending_reveals.remove(&client_id);
+        self.visible.clients.remove(&client_id);
+        self.visible.links.remove_client(client_id);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Caches internal buffers for mapping attribute-based visibility to replicon's entity-based visibility.
 #[derive(Resource)]
 pub(crate) struct VisibilityCache
@@ -48,71 +751,331 @@ pub(crate) struct VisibilityCache
     /// Attribute id sets cached for use by future clients.
     attribute_ids_buffer: Vec<HashSet<VisibilityAttributeId>>,
 
-    /// Server id if the server is treated as a player.
-    server_id: Option<u64>,
-}
+    /// Server id if the server is treated as a player.
+    server_id: Option<u64>,
+
+    /// Output stage for visibility changes.
+    output: VisibilityOutput,
+
+    /// Sources of granted client attributes.
+    grants: AttributeGrants,
+
+    /// Pending client attribute changes.
+    schedule: AttributeSchedule,
+
+    /// Visibility of entities with predicate or parameter conditions.
+    predicates: PredicateVisibility,
+}
+
+impl VisibilityCache
+{
+    /// Makes a new cache.
+    pub(crate) fn new() -> Self
+    {
+        Self{
+            attributes: HashMap::default(),
+            entities: EntityHashMap::default(),
+            conditions: HashMap::default(),
+            clients: HashMap::default(),
+            condition_ids_buffer: Vec::default(),
+            entities_buffer: Vec::default(),
+            client_ids_buffer: Vec::default(),
+            attribute_ids_buffer: Vec::default(),
+            server_id: None,
+            output: VisibilityOutput::default(),
+            grants: AttributeGrants::default(),
+            schedule: AttributeSchedule::default(),
+            predicates: PredicateVisibility::default(),
+        }
+    }
+
+    /// Adds a special 'server player' that is always considered connected.
+    pub(crate) fn add_server_as_client(
+        &mut self,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        server_id: u64,
+        default_attributes: &[VisibilityAttributeId],
+    ){
+        self.server_id = Some(server_id);
+        self.reset_client(client_entities, None, server_id, default_attributes);
+    }
+
+    /// Gets the server player's client id.
+    pub(crate) fn server_id(&self) -> Option<u64>
+    {
+        self.server_id
+    }
+
+    /// Adds an attribute to a client.
+    ///
+    /// Direct adds count as a source of granted attributes (see [`Self::revoke_client_attribute`]).
+    pub(crate) fn add_client_attribute(
+        &mut self,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        client_entity: Option<Entity>,
+        client_id: u64,
+        attribute: VisibilityAttributeId,
+    ){
+        self.grants.set_direct(client_id, attribute, true);
+        self.update_client_visibility(client_entities, client_entity, client_id, attribute, UpdateType::Insert);
+    }
+
+    /// Removes an attribute from a client.
+    ///
+    /// Granted attributes are kept until their sources are revoked.
+    pub(crate) fn remove_client_attribute(
+        &mut self,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        client_entity: Option<Entity>,
+        client_id: u64,
+        attribute: VisibilityAttributeId,
+    ){
+        if self.grants.is_granted(client_id, attribute)
+        {
+            tracing::trace!(?client_id, ?attribute, "kept granted attribute on remove");
+            self.grants.set_direct(client_id, attribute, false);
+            return;
+        }
+        self.update_client_visibility(client_entities, client_entity, client_id, attribute, UpdateType::Remove);
+    }
+
+    /// Replaces a client's attributes with `desired`.
+    ///
+    /// If `scope` is set then only attributes of that type are replaced, and `desired` should only contain attributes
+    /// of that type. Granted attributes are not removed.
+    ///
+    /// Only the differences between the current and desired attributes are applied, and conditions affected by the
+    /// differences are evaluated once.
+    pub(crate) fn sync_client_attributes(
+        &mut self,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        client_entity: Option<Entity>,
+        client_id: u64,
+        desired: HashSet<VisibilityAttributeId>,
+        scope: Option<TypeId>,
+    ){
+        // Access client attributes.
+        let client_attributes = self.clients
+            .entry(client_id)
+            .or_insert_with(|| self.attribute_ids_buffer.pop().unwrap_or_default());
+
+        // Collect conditions and bound entities affected by attribute differences.
+        let mut affected = self.condition_ids_buffer.pop().unwrap_or_default();
+        let mut bound = Vec::default();
+        let in_scope = |a: &VisibilityAttributeId| scope.is_none_or(|t| a.type_id() == t);
+        client_attributes.retain(
+            |attribute|
+            {
+                if !in_scope(attribute) || desired.contains(attribute) { return true; }
+                if self.grants.is_granted(client_id, *attribute)
+                {
+                    self.grants.set_direct(client_id, *attribute, false);
+                    return true;
+                }
+                tracing::trace!(?client_id, ?attribute, "removed attribute from client");
+                self.schedule.cancel_timed_removals(client_id, *attribute);
+                if let Some(condition_ids) = self.attributes.get(attribute) { affected.extend(condition_ids.iter().copied()); }
+                self.predicates.extend_bound_entities(attribute, &mut bound);
+                false
+            }
+        );
+        for attribute in desired
+        {
+            if !in_scope(&attribute)
+            { tracing::warn!(?client_id, ?attribute, "ignoring synced attribute outside of sync scope"); continue; }
+            self.grants.set_direct(client_id, attribute, true);
+            if !client_attributes.insert(attribute) { continue; }
+            tracing::trace!(?client_id, ?attribute, "inserted attribute to client");
+            if let Some(condition_ids) = self.attributes.get(&attribute) { affected.extend(condition_ids.iter().copied()); }
+            self.predicates.extend_bound_entities(&attribute, &mut bound);
+        }
+
+        // Get client visibility settings.
+        let mut visibility_settings = client_entity.and_then(|e| client_entities.get_mut(e).ok());
+
+        // Evaluate entities whose bound parameters contain the affected attributes.
+        // - Ignore disconnected clients and the server-client.
+        if let Some(ref mut visibility_settings) = visibility_settings
+        {
+            bound.sort_unstable();
+            bound.dedup();
+            self.predicates.update_bound_entities(
+                &mut self.output,
+                visibility_settings,
+                client_id,
+                &bound,
+                |entity|
+                {
+                    let condition_id = self.entities.get(&entity)?;
+                    self.conditions.get(condition_id).map(|(condition, _, _)| (*condition_id, condition))
+                },
+                client_attributes,
+            );
+        }
+
+        // Update the entity and client sets attached to each affected condition.
+        for condition_id in affected.drain()
+        {
+            let Some((condition, entities, clients)) = self.conditions.get_mut(&condition_id)
+            else { tracing::error!(?client_id, "missing condition on sync client attributes"); continue; };
+
+            // Evaluate conditions with predicates separately for each entity.
+            // - Ignore disconnected clients and the server-client.
+            if self.predicates.has(&condition_id)
+            {
+                let Some(ref mut visibility_settings) = visibility_settings else { continue; };
+                self.predicates.update_entities(
+                    &mut self.output, visibility_settings, client_id, condition, entities, client_attributes
+                );
+                continue;
+            }
+
+            // Evaluate client visibility for this condition.
+            let visibility = condition.evaluate(|a| client_attributes.contains(&a));
+
+            // Save the client's visibility of this condition.
+            match visibility
+            {
+                true  => { clients.insert(client_id); }
+                false => { clients.remove(&client_id); }
+            }
+
+            // Set visibility for entities attached to this condition.
+            // - Ignore disconnected clients and the server-client.
+            let Some(ref mut visibility_settings) = visibility_settings else { continue; };
+            tracing::trace!(?client_id, ?entities, ?condition, "visibility {visibility}");
 
-impl VisibilityCache
-{
-    /// Makes a new cache.
-    pub(crate) fn new() -> Self
-    {
-        Self{
-            attributes: HashMap::default(),
-            entities: EntityHashMap::default(),
-            conditions: HashMap::default(),
-            clients: HashMap::default(),
-            condition_ids_buffer: Vec::default(),
-            entities_buffer: Vec::default(),
-            client_ids_buffer: Vec::default(),
-            attribute_ids_buffer: Vec::default(),
-            server_id: None,
+            for entity in entities.iter()
+            {
+                self.output.set_visibility(visibility_settings, client_id, *entity, visibility);
+            }
         }
-    }
-
-    /// Adds a special 'server player' that is always considered connected.
-    pub(crate) fn add_server_as_client(
-        &mut self,
-        client_entities: &mut Query<&mut ClientVisibility>,
-        server_id: u64,
-    ){
-        self.server_id = Some(server_id);
-        self.reset_client(client_entities, None, server_id);
-    }
 
-    /// Gets the server player's client id.
-    pub(crate) fn server_id(&self) -> Option<u64>
-    {
-        self.server_id
+        // Cache the conditions buffer for future use.
+        self.condition_ids_buffer.push(affected);
     }
 
-    /// Adds an attribute to a client.
-    pub(crate) fn add_client_attribute(
+    /// Grants an attribute to a client from a source.
+    ///
+    /// The attribute is added to the client if it doesn't have it. If the client already has the attribute without
+    /// any grants, then it was added directly and the direct add is kept as a source.
+    pub(crate) fn grant_client_attribute(
         &mut self,
         client_entities: &mut Query<&mut ClientVisibility>,
         client_entity: Option<Entity>,
         client_id: u64,
         attribute: VisibilityAttributeId,
+        source: AttributeSource,
     ){
+        let direct = !self.grants.is_granted(client_id, attribute)
+            && self.clients.get(&client_id).is_some_and(|attributes| attributes.contains(&attribute));
+        self.grants.grant(client_id, attribute, source);
+        if direct { self.grants.set_direct(client_id, attribute, true); }
         self.update_client_visibility(client_entities, client_entity, client_id, attribute, UpdateType::Insert);
     }
 
-    /// Removes an attribute from a client.
-    pub(crate) fn remove_client_attribute(
+    /// Revokes a source's grant of an attribute to a client.
+    ///
+    /// The attribute is removed from the client if it has no remaining sources. Direct adds count as a source.
+    pub(crate) fn revoke_client_attribute(
         &mut self,
         client_entities: &mut Query<&mut ClientVisibility>,
         client_entity: Option<Entity>,
         client_id: u64,
         attribute: VisibilityAttributeId,
+        source: &AttributeSource,
     ){
+        if !self.grants.revoke(client_id, attribute, source) { return; }
         self.update_client_visibility(client_entities, client_entity, client_id, attribute, UpdateType::Remove);
     }
 
+    /// Revokes all grants from a source.
+    ///
+    /// Attributes are removed from clients if they have no remaining sources. Direct adds count as a source. The
+    /// client entities of affected clients are looked up with `client_entity`.
+    pub(crate) fn revoke_all_client_attributes_from(
+        &mut self,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        client_entity: impl Fn(u64) -> Option<Entity>,
+        source: &AttributeSource,
+    ){
+        for (client_id, attribute) in self.grants.revoke_all_from(source)
+        {
+            self.update_client_visibility(client_entities, (client_entity)(client_id), client_id, attribute, UpdateType::Remove);
+        }
+    }
+
+    /// Iterates the sources of a client's granted attribute.
+    pub(crate) fn iter_attribute_sources(
+        &self,
+        client_id: u64,
+        attribute: VisibilityAttributeId
+    ) -> impl Iterator<Item = &AttributeSource> + '_
+    {
+        self.grants.iter_sources(client_id, attribute)
+    }
+
+    /// Schedules a client attribute change.
+    ///
+    /// Changes are ignored for clients without attributes so pending changes can't re-add removed clients.
+    pub(crate) fn schedule_client_attribute(&mut self, scheduled: ScheduledAttribute)
+    {
+        if !self.clients.contains_key(&scheduled.client_id)
+        { tracing::warn!(?scheduled, "ignoring scheduled attribute change for unknown client"); return; }
+        tracing::trace!(?scheduled, "scheduled client attribute change");
+        self.schedule.schedule(scheduled);
+    }
+
+    /// Cancels pending timed removals of a client attribute.
+    pub(crate) fn cancel_timed_removals(&mut self, client_id: u64, attribute: VisibilityAttributeId)
+    {
+        self.schedule.cancel_timed_removals(client_id, attribute);
+    }
+
+    /// Applies scheduled client attribute changes that are due.
+    pub(crate) fn apply_scheduled_attributes(
+        &mut self,
+        id_map: &NetworkIdMap,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        elapsed: Duration,
+        tick: RepliconTick,
+    ){
+        if self.schedule.is_empty() { return; }
+
+        for scheduled in self.schedule.take_due(elapsed, tick)
+        {
+            let client_entity = id_map.get(&NetworkId::new(scheduled.client_id)).copied();
+            match scheduled.change
+            {
+                ScheduledChange::Add =>
+                { self.add_client_attribute(client_entities, client_entity, scheduled.client_id, scheduled.attribute); }
+                ScheduledChange::Remove =>
+                { self.remove_client_attribute(client_entities, client_entity, scheduled.client_id, scheduled.attribute); }
+            }
+        }
+    }
+
+    /// Removes a disconnected client.
+    ///
+    /// Scheduled changes are dropped, and grants are kept until they are revoked.
+    pub(crate) fn disconnect_client(&mut self, client_id: u64)
+    {
+        self.remove_client(client_id);
+        self.schedule.remove_client(client_id);
+    }
+
     /// Removes a client.
+    ///
+    /// Grants and scheduled changes are kept so they can be re-applied if the client is reset.
     pub(crate) fn remove_client(&mut self, client_id: u64)
     {
         tracing::debug!(?client_id, "removing client");
 
+        // Remove client output state.
+        self.output.overrides.remove(&client_id);
+        self.output.reset_client(client_id);
+        self.predicates.remove_client(client_id);
+
         // Remove client entry
         let Some(mut attribute_ids) = self.clients.remove(&client_id) else { return; };
 
@@ -140,34 +1103,60 @@ impl VisibilityCache
 
     /// Repairs a client by refreshing visibility of all entities in the [`ReplicatedClients`].
     ///
-    /// If this is a new client, the builtin [`Global`] and [`Client`] attributes will be inserted.
-    pub(crate) fn repair_client(&mut self, client_entities: &mut Query<&mut ClientVisibility>, client_entity: Option<Entity>, client_id: u64)
-    {
+    /// The client's default attributes will be inserted (see [`VisibilitySettings::with_default_attribute`]). Default
+    /// attributes count as direct adds of granted attributes.
+    pub(crate) fn repair_client(
+        &mut self,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        client_entity: Option<Entity>,
+        client_id: u64,
+        default_attributes: &[VisibilityAttributeId],
+    ){
+        for attribute in default_attributes.iter() { self.grants.set_direct(client_id, *attribute, true); }
+        self.restore_client(client_entities, client_entity, client_id, default_attributes);
+    }
+
+    /// Inserts attributes to a client and refreshes visibility of all entities in the [`ReplicatedClients`].
+    fn restore_client(
+        &mut self,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        client_entity: Option<Entity>,
+        client_id: u64,
+        default_attributes: &[VisibilityAttributeId],
+    ){
         tracing::debug!(?client_id, "repairing client");
+        self.predicates.unevaluated = true;
 
         // Access client attributes.
         let client_attributes = self.clients
             .entry(client_id)
             .or_insert_with(|| self.attribute_ids_buffer.pop().unwrap_or_default());
 
-        // Initialize with Global and Client attributes.
-        client_attributes.insert(Global.attribute_id());
-        client_attributes.insert(Client(client_id).attribute_id());
+        // Initialize with default attributes.
+        client_attributes.extend(default_attributes.iter().copied());
 
         // Skip repair for server-clients, who we assume only need to be set up once.
         // - Also note that server-clients don't have entries in ReplicatedClients.
         if Some(client_id) == self.server_id { return; }
 
         // Get client visibility settings.
-        let Some(mut visibility_settings) = client_entity.map(|e| client_entities.get_mut(e).ok()).flatten()
+        let Some(mut visibility_settings) = client_entity.and_then(|e| client_entities.get_mut(e).ok())
         else { tracing::error!(?client_id, "repairing client is missing from client cache"); return; };
 
+        // Clear output state that refers to the client's previous visibility.
+        self.output.reset_client(client_id);
+
         // Prep evaluator
         let self_conditions = &mut self.conditions;
+        let output = &mut self.output;
+        let predicates = &self.predicates;
         let mut evaluator = |condition_id: &VisibilityConditionId| -> bool
         {
             let Some((condition, entities, clients)) = self_conditions.get_mut(condition_id) else { return false; };
 
+            // Conditions with predicates are evaluated below.
+            if predicates.has(condition_id) { return true; }
+
             // Evaluate client visibility for this condition.
             let visibility = condition.evaluate(|a| client_attributes.contains(&a));
 
@@ -184,7 +1173,7 @@ impl VisibilityCache
 
             for entity in entities.iter()
             {
-                visibility_settings.set_visibility(*entity, visibility);
+                output.set_visibility(&mut visibility_settings, client_id, *entity, visibility);
             }
 
             true
@@ -194,7 +1183,7 @@ impl VisibilityCache
         for client_attribute in client_attributes.iter()
         {
             // Access conditions associated with this attribute.
-            let Some(condition_ids) = self.attributes.get(&client_attribute) else { continue; };
+            let Some(condition_ids) = self.attributes.get(client_attribute) else { continue; };
 
             // Update the client sets attached to each condition.
             for condition_id in condition_ids.iter()
@@ -203,18 +1192,41 @@ impl VisibilityCache
                 { tracing::error!(?client_id, ?condition_id, "missing condition on repair client visibility"); }
             }
         }
+
+        // Evaluate conditions with predicates.
+        // - These may not depend on the client's attributes.
+        let condition_ids: Vec<_> = self.predicates.conditions.keys().copied().collect();
+        for condition_id in condition_ids.iter()
+        {
+            let Some((condition, entities, _)) = self.conditions.get(condition_id) else { continue; };
+            self.predicates.update_entities(
+                &mut self.output, &mut visibility_settings, client_id, condition, entities, client_attributes
+            );
+        }
+
+        // Reapply overrides.
+        // - Overridden entities may not have conditions that depend on the client's attributes.
+        let output = &mut self.output;
+        let Some(overrides) = output.overrides.get(&client_id) else { return; };
+        for (entity, visibility) in overrides.iter()
+        {
+            output.visible.set(&output.overrides, &mut visibility_settings, client_id, *entity, *visibility);
+        }
     }
 
     /// Resets a client client in the cache.
     ///
     /// If the client already has registered attributes, they are cleared.
     ///
-    /// The client will start with the builtin [`Global`] and [`Client`] attributes.
+    /// The client will start with its default attributes (see [`VisibilitySettings::with_default_attribute`]),
+    /// attributes granted to it by sources that were not revoked, and timed attributes that have not expired. Other
+    /// scheduled changes stay pending.
     pub(crate) fn reset_client(
         &mut self,
         client_entities: &mut Query<&mut ClientVisibility>,
         client_entity: Option<Entity>,
         client_id: u64,
+        default_attributes: &[VisibilityAttributeId],
     ){
         tracing::debug!(?client_id, "resetting client");
 
@@ -222,8 +1234,15 @@ impl VisibilityCache
         self.remove_client(client_id);
 
         // Reinitialize the client that was just removed.
-        // - This calculates visibility using the default visibility attributes (Global and Client).
-        self.repair_client(client_entities, client_entity, client_id);
+        // - This calculates visibility using the default visibility attributes, granted attributes, and timed
+        //   attributes.
+        // - Default and timed attributes count as direct adds of granted attributes.
+        let mut attributes = default_attributes.to_vec();
+        attributes.extend(self.schedule.iter_timed_attributes(client_id));
+        self.grants.clear_direct(client_id);
+        for attribute in attributes.iter() { self.grants.set_direct(client_id, *attribute, true); }
+        attributes.extend(self.grants.iter_attributes(client_id));
+        self.restore_client(client_entities, client_entity, client_id, &attributes);
     }
 
     /// Updates an entity's visibility condition.
@@ -252,6 +1271,7 @@ impl VisibilityCache
 
         if is_new_condition
         {
+            self.predicates.add_condition(condition_id, condition);
             for attribute_id in condition.iter_attributes()
             {
                 if !self.attributes
@@ -262,6 +1282,9 @@ impl VisibilityCache
             }
         }
 
+        // Drop predicate results that were kept while changing conditions.
+        self.predicates.retain_entity_results(entity, condition_id);
+
         // Update conditions map.
         let (_, entities, ref mut clients) = entry
             .or_insert_with(
@@ -279,6 +1302,24 @@ impl VisibilityCache
         if !entities.insert(entity)
         { tracing::error!(?entity, ?condition, "entity unexpectedly in tracked entities for condition"); }
 
+        // Evaluate conditions with predicates for this entity.
+        // - We ignore server-clients who can see all entities automatically.
+        if self.predicates.has(&condition_id)
+        {
+            self.predicates.unevaluated = true;
+            for (client_id, attributes) in self
+                .clients
+                .iter()
+                .filter(|(id, _)| Some(**id) != self.server_id)
+            {
+                let Some(client_entity) = id_map.get(&NetworkId::new(*client_id)) else { continue };
+                let Some(mut client_visibility) = client_entities.get_mut(*client_entity).ok() else { continue; };
+                if !self.predicates.evaluate(condition, attributes, *client_id, entity) { continue; }
+                self.predicates.update_entity(&mut self.output, &mut client_visibility, *client_id, condition, entity, attributes);
+            }
+            return;
+        }
+
         // Update clients
         match is_new_condition
         {
@@ -296,7 +1337,7 @@ impl VisibilityCache
                     if !condition.evaluate(|a| attributes.contains(&a)) { continue }
                     tracing::trace!(?client_id, ?entity, ?condition, "visibility <true> new condition");
                     clients.insert(*client_id);
-                    client_visibility.set_visibility(entity, true);
+                    self.output.set_visibility(&mut client_visibility, *client_id, entity, true);
                 }
             }
             // Update visibility of this entity for clients that can see this condition.
@@ -311,7 +1352,7 @@ impl VisibilityCache
                     let Some(client_entity) = id_map.get(&NetworkId::new(*client_id)) else { continue };
                     let Some(mut client_visibility) = client_entities.get_mut(*client_entity).ok() else { continue; };
                     tracing::trace!(?client_id, ?entity, ?condition, "visibility <true>");
-                    client_visibility.set_visibility(entity, true);
+                    self.output.set_visibility(&mut client_visibility, *client_id, entity, true);
                 }
             }
         }
@@ -327,6 +1368,295 @@ impl VisibilityCache
         self.remove_entity_with_check(id_map, client_entities, entity, None);
     }
 
+    /// Sets or clears a client's visibility override for an entity.
+    ///
+    /// Overrides take precedence over the entity's visibility condition.
+    pub(crate) fn set_visibility_override(
+        &mut self,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        client_entity: Option<Entity>,
+        client_id: u64,
+        entity: Entity,
+        visibility: Option<bool>,
+    ){
+        // Update the override.
+        match visibility
+        {
+            Some(visibility) =>
+            {
+                let overrides = self.output.overrides.entry(client_id).or_default();
+                if overrides.insert(entity, visibility) == Some(visibility) { return; }
+                tracing::trace!(?client_id, ?entity, "visibility override <{visibility}>");
+            }
+            None =>
+            {
+                let Some(overrides) = self.output.overrides.get_mut(&client_id) else { return; };
+                if overrides.remove(&entity).is_none() { return; }
+                if overrides.is_empty() { self.output.overrides.remove(&client_id); }
+                tracing::trace!(?client_id, ?entity, "cleared visibility override");
+            }
+        }
+
+        // Update the client's visibility.
+        // - Ignore disconnected clients and the server-client.
+        if Some(client_id) == self.server_id { return; }
+        let Some(mut client_visibility) = client_entity.and_then(|e| client_entities.get_mut(e).ok()) else { return; };
+        let condition_visibility = self.condition_visibility(client_id, entity);
+        self.output.set_visibility(&mut client_visibility, client_id, entity, condition_visibility);
+    }
+
+    /// Clears all visibility overrides of a client.
+    pub(crate) fn clear_visibility_overrides(
+        &mut self,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        client_entity: Option<Entity>,
+        client_id: u64,
+    ){
+        let Some(overrides) = self.output.overrides.remove(&client_id) else { return; };
+        tracing::trace!(?client_id, "cleared visibility overrides");
+
+        // Update the client's visibility.
+        // - Ignore disconnected clients and the server-client.
+        if Some(client_id) == self.server_id { return; }
+        let Some(mut client_visibility) = client_entity.and_then(|e| client_entities.get_mut(e).ok()) else { return; };
+        for entity in overrides.into_keys()
+        {
+            let condition_visibility = self.condition_visibility(client_id, entity);
+            self.output.set_visibility(&mut client_visibility, client_id, entity, condition_visibility);
+        }
+    }
+
+    /// Checks if a client satisfies an entity's condition.
+    fn condition_visibility(&self, client_id: u64, entity: Entity) -> bool
+    {
+        self.predicates.is_visible(client_id, entity) || self.entities
+            .get(&entity)
+            .and_then(|condition_id| self.conditions.get(condition_id))
+            .is_some_and(|(_, _, clients)| clients.contains(&client_id))
+    }
+
+    /// Sets or removes the link of an entity whose visibility follows another entity.
+    ///
+    /// Linked entities don't linger or wait for the reveal budget, since they follow their anchor's output.
+    pub(crate) fn set_entity_link(
+        &mut self,
+        id_map: &NetworkIdMap,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        entity: Entity,
+        link: Option<VisibilityLink>,
+    ){
+        if !self.output.visible.links.set_link(entity, link) { return; }
+        tracing::trace!(?entity, ?link, "set visibility link");
+
+        // Refresh the entity's visibility.
+        // - Ignore disconnected clients and the server-client.
+        for client_id in self.clients.keys().filter(|id| Some(**id) != self.server_id)
+        {
+            let Some(client_entity) = id_map.get(&NetworkId::new(*client_id)) else { continue };
+            let Some(mut client_visibility) = client_entities.get_mut(*client_entity).ok() else { continue; };
+            if link.is_some()
+            {
+                self.output.cancel_linger(*client_id, entity);
+                self.output.cancel_reveal(*client_id, entity);
+            }
+            let condition_visibility = self.condition_visibility(*client_id, entity);
+            self.output.set_visibility(&mut client_visibility, *client_id, entity, condition_visibility);
+        }
+    }
+
+    /// Sets or removes an entity's binding of a condition parameter.
+    ///
+    /// The entity is re-evaluated for all clients if its condition uses the parameter.
+    pub(crate) fn set_entity_binding(
+        &mut self,
+        id_map: &NetworkIdMap,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        entity: Entity,
+        param: VisibilityParamId,
+        binding: Option<VisibilityCondition>,
+    ){
+        if !self.predicates.set_binding(entity, param, binding) { return; }
+        tracing::trace!(?entity, ?param, "set parameter binding");
+        self.predicates.unevaluated = true;
+
+        // Access the entity's condition.
+        let Some(condition_id) = self.entities.get(&entity).copied() else { return; };
+        if !self.predicates.has_param(&condition_id, param) { return; }
+        let Some((condition, _, _)) = self.conditions.get(&condition_id) else { return; };
+
+        // Drop predicate results that the entity no longer uses.
+        self.predicates.retain_entity_results(entity, condition_id);
+
+        // Re-evaluate the entity.
+        // - Ignore disconnected clients and the server-client.
+        for (client_id, attributes) in self.clients.iter().filter(|(id, _)| Some(**id) != self.server_id)
+        {
+            let Some(client_entity) = id_map.get(&NetworkId::new(*client_id)) else { continue };
+            let Some(mut client_visibility) = client_entities.get_mut(*client_entity).ok() else { continue; };
+            self.predicates.update_entity(&mut self.output, &mut client_visibility, *client_id, condition, entity, attributes);
+        }
+    }
+
+    /// Gets a client's visibility override for an entity.
+    pub(crate) fn visibility_override(&self, client_id: u64, entity: Entity) -> Option<bool>
+    {
+        self.output.get_override(client_id, entity)
+    }
+
+    /// Removes all output state for an entity.
+    pub(crate) fn remove_entity_output(&mut self, entity: Entity)
+    {
+        self.output.overrides.retain(
+            |_, overrides|
+            {
+                overrides.remove(&entity);
+                !overrides.is_empty()
+            }
+        );
+        self.output.lingering.retain(
+            |_, lingering|
+            {
+                lingering.remove(&entity);
+                !lingering.is_empty()
+            }
+        );
+        self.output.pending_reveals.retain(
+            |_, pending|
+            {
+                pending.remove(&entity);
+                !pending.is_empty()
+            }
+        );
+        self.output.visible.remove_entity(entity);
+    }
+
+    /// Sets the linger applied to entities without their own linger.
+    pub(crate) fn set_default_linger(&mut self, linger: Option<VisibilityLinger>)
+    {
+        self.output.default_linger = linger;
+    }
+
+    /// Sets or clears an entity's linger.
+    ///
+    /// Pending lingers are not affected.
+    pub(crate) fn set_entity_linger(&mut self, entity: Entity, linger: Option<VisibilityLinger>)
+    {
+        match linger
+        {
+            Some(linger) => { self.output.entity_linger.insert(entity, linger); }
+            None         => { self.output.entity_linger.remove(&entity); }
+        }
+    }
+
+    /// Sets the budget for revealing entities to clients.
+    pub(crate) fn set_budget(&mut self, budget: VisibilityBudget)
+    {
+        self.output.budget = budget;
+        self.output.visible.enabled = budget.max_visible.is_some();
+    }
+
+    /// Sets or clears an entity's reveal priority.
+    pub(crate) fn set_entity_priority(&mut self, entity: Entity, priority: Option<i32>)
+    {
+        match priority
+        {
+            Some(priority) => { self.output.entity_priority.insert(entity, priority); }
+            None           => { self.output.entity_priority.remove(&entity); }
+        }
+    }
+
+    /// Returns `true` if clients or entities were added since the last call, which means some predicates may not have
+    /// been evaluated for them yet.
+    pub(crate) fn take_unevaluated_predicates(&mut self) -> bool
+    {
+        std::mem::take(&mut self.predicates.unevaluated)
+    }
+
+    /// Collects (client, entity, predicate) combinations that need to be evaluated.
+    ///
+    /// `needs_evaluation` receives each predicate and whether it has a result for the client and entity.
+    pub(crate) fn predicate_work(
+        &self,
+        is_connected: impl Fn(u64) -> bool,
+        needs_evaluation: impl Fn(VisibilityPredicateId, bool) -> bool,
+    ) -> Vec<(u64, Entity, VisibilityPredicateId)>
+    {
+        let mut work = Vec::default();
+        let mut predicates = Vec::default();
+        for condition_id in self.predicates.conditions.keys()
+        {
+            let Some((_, entities, _)) = self.conditions.get(condition_id) else { continue; };
+            for entity in entities.iter()
+            {
+                // Collect predicates of the condition and the entity's bound parameters.
+                predicates.clear();
+                for predicate in self.predicates.iter_entity_predicates(condition_id, *entity)
+                {
+                    if predicates.contains(&predicate) { continue; }
+                    predicates.push(predicate);
+                }
+                if predicates.is_empty() { continue; }
+
+                for client_id in self.clients.keys().filter(|id| Some(**id) != self.server_id && is_connected(**id))
+                {
+                    let results = self.predicates.results.get(client_id).and_then(|r| r.get(entity));
+                    for predicate in predicates.iter()
+                    {
+                        let has_result = results.is_some_and(|r| r.contains_key(predicate));
+                        if !needs_evaluation(*predicate, has_result) { continue; }
+                        work.push((*client_id, *entity, *predicate));
+                    }
+                }
+            }
+        }
+        work
+    }
+
+    /// Saves predicate results, and updates visibility of entities whose results changed.
+    pub(crate) fn apply_predicate_results(
+        &mut self,
+        id_map: &NetworkIdMap,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        results: impl IntoIterator<Item = (u64, Entity, VisibilityPredicateId, bool)>,
+    ){
+        let mut changed = HashSet::<(u64, Entity)>::default();
+        for (client_id, entity, predicate, result) in results
+        {
+            let prev = self.predicates.results
+                .entry(client_id)
+                .or_default()
+                .entry(entity)
+                .or_default()
+                .insert(predicate, result);
+            if prev == Some(result) { continue; }
+            changed.insert((client_id, entity));
+        }
+
+        for (client_id, entity) in changed
+        {
+            let Some(condition_id) = self.entities.get(&entity) else { continue; };
+            let Some((condition, _, _)) = self.conditions.get(condition_id) else { continue; };
+            let Some(attributes) = self.clients.get(&client_id) else { continue; };
+            let Some(client_entity) = id_map.get(&NetworkId::new(client_id)) else { continue };
+            let Some(mut client_visibility) = client_entities.get_mut(*client_entity).ok() else { continue; };
+
+            let visibility = self.predicates.evaluate(condition, attributes, client_id, entity);
+            if visibility == self.predicates.is_visible(client_id, entity) { continue; }
+            self.predicates.update_entity(&mut self.output, &mut client_visibility, client_id, condition, entity, attributes);
+        }
+    }
+
+    /// Hides lingering entities whose linger has expired, then admits pending reveals.
+    pub(crate) fn update_output(
+        &mut self,
+        id_map: &NetworkIdMap,
+        client_entities: &mut Query<&mut ClientVisibility>,
+        elapsed: Duration,
+        tick: RepliconTick,
+    ){
+        self.output.update(id_map, client_entities, elapsed, tick);
+    }
+
     /// Accesses a client's attributes.
     pub(crate) fn client_attributes(&self, client_id: u64) -> Option<&HashSet<VisibilityAttributeId>>
     {
@@ -342,7 +1672,7 @@ impl VisibilityCache
     {
         self.clients
             .get(&client_id)
-            .map_or(false, |attrs| condition.evaluate(|a| attrs.contains(&a)))
+            .is_some_and(|attrs| condition.evaluate(|a| attrs.contains(&a)))
     }
 
     /// Iterates a client's attributes.
@@ -350,7 +1680,7 @@ impl VisibilityCache
     {
         self.clients
             .get(&client_id)
-            .map(|a| a.iter().map(|a| *a))
+            .map(|a| a.iter().copied())
             .into_iter()
             .flatten()
     }
@@ -407,18 +1737,49 @@ impl VisibilityCache
             }
         }
 
+        // Get client visibility settings.
+        let mut visibility_settings = client_entity.and_then(|e| client_entities.get_mut(e).ok());
+
+        // Evaluate entities whose bound parameters contain this attribute.
+        // - Ignore disconnected clients and the server-client.
+        if let Some(ref mut visibility_settings) = visibility_settings
+        {
+            let mut bound = Vec::default();
+            self.predicates.extend_bound_entities(&attribute, &mut bound);
+            self.predicates.update_bound_entities(
+                &mut self.output,
+                visibility_settings,
+                client_id,
+                &bound,
+                |entity|
+                {
+                    let condition_id = self.entities.get(&entity)?;
+                    self.conditions.get(condition_id).map(|(condition, _, _)| (*condition_id, condition))
+                },
+                client_attributes,
+            );
+        }
+
         // Access conditions associated with this attribute.
         let Some(condition_ids) = self.attributes.get(&attribute) else { return; };
 
-        // Get client visibility settings.
-        let mut visibility_settings = client_entity.map(|e| client_entities.get_mut(e).ok()).flatten();
-
         // Update the entity and client sets attached to each condition.
         for condition_id in condition_ids.iter()
         {
             let Some((condition, entities, clients)) = self.conditions.get_mut(condition_id)
             else { tracing::error!(?client_id, "missing condition on update client visibility"); continue; };
 
+            // Evaluate conditions with predicates separately for each entity.
+            // - Ignore disconnected clients and the server-client.
+            if self.predicates.has(condition_id)
+            {
+                let Some(ref mut visibility_settings) = visibility_settings else { continue; };
+                self.predicates.update_entities(
+                    &mut self.output, visibility_settings, client_id, condition, entities, client_attributes
+                );
+                continue;
+            }
+
             // Evaluate client visibility for this condition.
             let visibility = condition.evaluate(|a| client_attributes.contains(&a));
 
@@ -436,7 +1797,7 @@ impl VisibilityCache
 
             for entity in entities.iter()
             {
-                visibility_settings.set_visibility(*entity, visibility);
+                self.output.set_visibility(visibility_settings, client_id, *entity, visibility);
             }
         }
     }
@@ -476,19 +1837,22 @@ impl VisibilityCache
 
         // Update visibility of this entity for clients that can see this condition.
         // - We skip disconnected clients and server-clients.
-        for client_id in clients.iter()
+        // - Predicate results are kept if the entity is only changing conditions.
+        let predicate_clients = self.predicates.remove_entity(entity, check_condition.is_none());
+        for client_id in clients.iter().chain(predicate_clients.iter())
         {
             let Some(client_entity) = id_map.get(&NetworkId::new(*client_id)) else { continue };
             let Some(mut client_visibility) = client_entities.get_mut(*client_entity).ok() else { continue; };
             tracing::trace!(?client_id, ?entity, ?condition, "visibility false");
-            client_visibility.set_visibility(entity, false);
+            self.output.set_visibility(&mut client_visibility, *client_id, entity, false);
         }
 
         // Cleanup
-        if entities.len() == 0
+        if entities.is_empty()
         {
             // remove condition
             let (condition, mut entities, mut clients) = self.conditions.remove(&condition_id).unwrap();
+            self.predicates.remove_condition(&condition_id);
 
             // remove condition from attributes map
             for attribute_id in condition.iter_attributes()
@@ -500,7 +1864,7 @@ impl VisibilityCache
                 { tracing::error!(?entity, ?condition_id, "missing condition on remove entity cleanup"); continue; }
 
                 // Cleanup
-                if condition_ids.len() == 0
+                if condition_ids.is_empty()
                 {
                     let mut condition_ids = self.attributes.remove(&attribute_id).unwrap();
                     condition_ids.clear();
@@ -7,15 +7,18 @@ use siphasher::sip128::{Hasher128, SipHasher13};
 use smallvec::SmallVec;
 
 //standard shortcuts
+use std::collections::HashMap;
 use std::hash::Hash;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, Weak};
 
 //-------------------------------------------------------------------------------------------------------------------
 //-------------------------------------------------------------------------------------------------------------------
 
-/// Evaluates a condition branch with the given root node inspector.
+/// Evaluates a condition branch with the given root node inspectors.
 fn evaluate(
     inspector    : &impl Fn(VisibilityAttributeId) -> bool,
+    predicates   : &impl Fn(VisibilityPredicateId) -> bool,
+    params       : &impl Fn(VisibilityParamId) -> bool,
     condition    : &[VisibilityConditionNode],
     current_node : usize
 ) -> bool
@@ -23,11 +26,181 @@ fn evaluate(
     let a = current_node + 1;
     match condition[current_node]
     {
-        VisibilityConditionNode::Empty      => { tracing::error!("found empty node during evaluation"); false },
-        VisibilityConditionNode::Attr(attr) => (inspector)(attr),
-        VisibilityConditionNode::Not        => !evaluate(inspector, condition, a),
-        VisibilityConditionNode::And(b)     => evaluate(inspector, condition, a) && evaluate(inspector, condition, b),
-        VisibilityConditionNode::Or(b)      => evaluate(inspector, condition, a) || evaluate(inspector, condition, b),
+        VisibilityConditionNode::Empty           => { tracing::error!("found empty node during evaluation"); false },
+        VisibilityConditionNode::Attr(attr)      => (inspector)(attr),
+        VisibilityConditionNode::Predicate(pred) => (predicates)(pred),
+        VisibilityConditionNode::Param(param)    => (params)(param),
+        VisibilityConditionNode::Not             => !evaluate(inspector, predicates, params, condition, a),
+        VisibilityConditionNode::And(b)          =>
+        {
+            evaluate(inspector, predicates, params, condition, a) && evaluate(inspector, predicates, params, condition, b)
+        }
+        VisibilityConditionNode::Or(b)           =>
+        {
+            evaluate(inspector, predicates, params, condition, a) || evaluate(inspector, predicates, params, condition, b)
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Evaluates a condition branch with three-valued logic, where `None` is an unknown value.
+///
+/// `or` is `true` if either side is `true`, `and` is `false` if either side is `false`, and `not` of an unknown
+/// value is unknown.
+fn evaluate_partial(
+    inspector    : &impl Fn(VisibilityAttributeId) -> bool,
+    predicates   : &impl Fn(VisibilityPredicateId) -> bool,
+    params       : &impl Fn(VisibilityParamId) -> Option<bool>,
+    condition    : &[VisibilityConditionNode],
+    current_node : usize
+) -> Option<bool>
+{
+    let a = current_node + 1;
+    match condition[current_node]
+    {
+        VisibilityConditionNode::Empty           => { tracing::error!("found empty node during evaluation"); Some(false) },
+        VisibilityConditionNode::Attr(attr)      => Some((inspector)(attr)),
+        VisibilityConditionNode::Predicate(pred) => Some((predicates)(pred)),
+        VisibilityConditionNode::Param(param)    => (params)(param),
+        VisibilityConditionNode::Not             => evaluate_partial(inspector, predicates, params, condition, a).map(|r| !r),
+        VisibilityConditionNode::And(b)          =>
+        {
+            match evaluate_partial(inspector, predicates, params, condition, a)
+            {
+                Some(false) => Some(false),
+                Some(true)  => evaluate_partial(inspector, predicates, params, condition, b),
+                None        => evaluate_partial(inspector, predicates, params, condition, b).filter(|r| !r),
+            }
+        }
+        VisibilityConditionNode::Or(b)           =>
+        {
+            match evaluate_partial(inspector, predicates, params, condition, a)
+            {
+                Some(true)  => Some(true),
+                Some(false) => evaluate_partial(inspector, predicates, params, condition, b),
+                None        => evaluate_partial(inspector, predicates, params, condition, b).filter(|r| *r),
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Minimum number of entries in the interner before dead entries are pruned.
+const MIN_INTERNER_PRUNE_THRESHOLD: usize = 64;
+
+/// Interned allocations of large conditions.
+static LARGE_CONDITION_INTERNER: Mutex<Option<LargeConditionInterner>> = Mutex::new(None);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Weak-ref table that lets identical large conditions share one allocation.
+struct LargeConditionInterner
+{
+    /// [ condition id : condition allocation ]
+    conditions: HashMap<VisibilityConditionId, Weak<[VisibilityConditionNode]>>,
+    /// Table size that will trigger pruning of dead entries.
+    prune_threshold: usize,
+}
+
+impl LargeConditionInterner
+{
+    /// Gets the interned allocation for a condition, or interns the condition if it is new.
+    fn intern(&mut self, id: VisibilityConditionId, condition: Arc<[VisibilityConditionNode]>) -> Arc<[VisibilityConditionNode]>
+    {
+        if let Some(interned) = self.conditions.get(&id).and_then(|c| c.upgrade()) { return interned; }
+
+        if self.conditions.len() >= self.prune_threshold
+        {
+            self.conditions.retain(|_, c| c.strong_count() > 0);
+            self.prune_threshold = MIN_INTERNER_PRUNE_THRESHOLD.max(self.conditions.len() * 2);
+        }
+
+        self.conditions.insert(id, Arc::downgrade(&condition));
+        condition
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the depth of a condition branch.
+fn depth(condition: &[VisibilityConditionNode], current_node: usize) -> usize
+{
+    let a = current_node + 1;
+    match condition[current_node]
+    {
+        VisibilityConditionNode::Empty        => 0,
+        VisibilityConditionNode::Attr(_)      => 1,
+        VisibilityConditionNode::Predicate(_) => 1,
+        VisibilityConditionNode::Param(_)     => 1,
+        VisibilityConditionNode::Not     => 1 + depth(condition, a),
+        VisibilityConditionNode::And(b)  |
+        VisibilityConditionNode::Or(b)   => 1 + depth(condition, a).max(depth(condition, b)),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Rebuilds a condition branch, replacing root nodes with the given replacer.
+fn rebuild_with_replacer<C: IntoVisibilityCondition>(
+    replacer     : &mut impl FnMut(VisibilityConditionNode) -> Option<C>,
+    condition    : &[VisibilityConditionNode],
+    current_node : usize,
+    mut builder  : VisibilityConditionBuilder,
+    count        : &mut usize,
+) -> VisibilityConditionBuilder
+{
+    let a = current_node + 1;
+    match condition[current_node]
+    {
+        VisibilityConditionNode::Empty =>
+        {
+            builder.push_empty(0);
+            builder
+        }
+        VisibilityConditionNode::Attr(attr) =>
+        {
+            let Some(replacement) = (replacer)(condition[current_node]) else { builder.push_attr_node(attr); return builder; };
+            *count += 1;
+            replacement.build(builder)
+        }
+        VisibilityConditionNode::Predicate(predicate) =>
+        {
+            let Some(replacement) = (replacer)(condition[current_node])
+            else { builder.push_predicate_node(predicate); return builder; };
+            *count += 1;
+            replacement.build(builder)
+        }
+        VisibilityConditionNode::Param(param) =>
+        {
+            let Some(replacement) = (replacer)(condition[current_node]) else { builder.push_param_node(param); return builder; };
+            *count += 1;
+            replacement.build(builder)
+        }
+        VisibilityConditionNode::Not =>
+        {
+            builder.push_not_node();
+            rebuild_with_replacer(replacer, condition, a, builder, count)
+        }
+        VisibilityConditionNode::And(b) =>
+        {
+            let and_node = builder.push_empty(2);
+            let mut builder = rebuild_with_replacer(replacer, condition, a, builder, count);
+            builder.set_and_node(and_node);
+            rebuild_with_replacer(replacer, condition, b, builder, count)
+        }
+        VisibilityConditionNode::Or(b) =>
+        {
+            let or_node = builder.push_empty(2);
+            let mut builder = rebuild_with_replacer(replacer, condition, a, builder, count);
+            builder.set_or_node(or_node);
+            rebuild_with_replacer(replacer, condition, b, builder, count)
+        }
     }
 }
 
@@ -53,6 +226,14 @@ pub enum VisibilityConditionNode
     Empty,
     /// Root node.
     Attr(VisibilityAttributeId),
+    /// Root node that is evaluated separately for each client and entity.
+    ///
+    /// See [`register_visibility_predicate`](VisibilityPredicateAppExt::register_visibility_predicate).
+    Predicate(VisibilityPredicateId),
+    /// Root node that is bound separately for each entity.
+    ///
+    /// See [`register_condition_param`](VisibilityParamsAppExt::register_condition_param).
+    Param(VisibilityParamId),
     /// Next node is child.
     Not,
     /// Next node is left branch. Records node of right branch.
@@ -80,6 +261,16 @@ impl VisibilityConditionNode
                 let Self::Attr(attr_other) = other else { return false; };
                 attr == attr_other
             }
+            Self::Predicate(pred) =>
+            {
+                let Self::Predicate(pred_other) = other else { return false; };
+                pred == pred_other
+            }
+            Self::Param(param) =>
+            {
+                let Self::Param(param_other) = other else { return false; };
+                param == param_other
+            }
             Self::Not =>
             {
                 let Self::Not = other else { return false; };
@@ -111,6 +302,9 @@ pub const SMALL_PACK_LEN: usize = 3;
 /// Constructing a condition only requires allocations if the condition contains more than [`SMALL_PACK_LEN`] nodes.
 /// Cloning a condition will *not* allocate.
 ///
+/// Large conditions are interned, so identical large conditions share one allocation (even if they were constructed
+/// separately). The allocation is freed when the last condition referencing it is dropped.
+///
 /// Use [`Self::evaluate`] to evaluate the condition.
 /// Note that empty conditions always evaluate to `false`.
 ///
@@ -119,6 +313,8 @@ pub const SMALL_PACK_LEN: usize = 3;
 /// - 2 nodes: `VisibilityCondition::new(not(InABush))`
 /// - 3 nodes: `VisibilityCondition::new(and(IsFast, IsSmall)`
 /// - 4 nodes: `VisibilityCondition::new(and(IsSwimming, not(WearingSwimsuit)))`
+// Equality is defined by the condition id, which is derived from this hash.
+#[allow(clippy::derived_hash_with_manual_eq)]
 #[derive(Component, Debug, Clone, Hash)]
 pub enum VisibilityCondition
 {
@@ -172,6 +368,42 @@ impl VisibilityCondition
         }
     }
 
+    /// Iterates predicates referenced in the condition tree.
+    pub fn iter_predicates(&self) -> impl Iterator<Item = VisibilityPredicateId> + '_
+    {
+        self.as_slice().iter().filter_map(
+            |n|
+            {
+                let VisibilityConditionNode::Predicate(pred) = n else { return None; };
+                Some(*pred)
+            }
+        )
+    }
+
+    /// Checks if the condition tree references any predicates.
+    pub fn has_predicates(&self) -> bool
+    {
+        self.iter_predicates().next().is_some()
+    }
+
+    /// Iterates parameters referenced in the condition tree.
+    pub fn iter_params(&self) -> impl Iterator<Item = VisibilityParamId> + '_
+    {
+        self.as_slice().iter().filter_map(
+            |n|
+            {
+                let VisibilityConditionNode::Param(param) = n else { return None; };
+                Some(*param)
+            }
+        )
+    }
+
+    /// Checks if the condition tree references any parameters.
+    pub fn has_params(&self) -> bool
+    {
+        self.iter_params().next().is_some()
+    }
+
     /// Accesses the inner condition tree as a sequence of nodes.
     pub fn as_slice(&self) -> &[VisibilityConditionNode]
     {
@@ -198,12 +430,53 @@ impl VisibilityCondition
     ///
     /// The evaluator should check if a given attribute is known. Modifiers (not/and/or) are automatically evaluated.
     ///
+    /// Predicate nodes evaluate to `false`. Use [`Self::evaluate_with_predicates`] to evaluate predicates.
+    /// Parameter nodes evaluate to `false`. Use [`Self::evaluate_with_params`] to evaluate parameters.
+    ///
     /// Returns `false` for empty conditions.
     pub fn evaluate(&self, evaluator: impl Fn(VisibilityAttributeId) -> bool) -> bool
+    {
+        self.evaluate_with_predicates(evaluator, |_| false)
+    }
+
+    /// Evaluates the condition tree with an attribute evaluator and a predicate evaluator.
+    ///
+    /// Parameter nodes evaluate to `false`.
+    ///
+    /// Returns `false` for empty conditions.
+    pub fn evaluate_with_predicates(
+        &self,
+        evaluator: impl Fn(VisibilityAttributeId) -> bool,
+        predicates: impl Fn(VisibilityPredicateId) -> bool,
+    ) -> bool
+    {
+        let slice = self.as_slice();
+        if slice.is_empty() { return false; }
+        evaluate(&evaluator, &predicates, &|_| false, slice, 0)
+    }
+
+    /// Evaluates the condition tree with an attribute evaluator, a predicate evaluator, and parameter bindings.
+    ///
+    /// Parameter nodes evaluate their bound conditions. Parameters that are unbound, or bound to conditions with
+    /// parameters, are unknown. Unknown values don't affect `or` nodes with a satisfied side or `and` nodes with an
+    /// unsatisfied side, and `not` of an unknown value is unknown. The condition is only satisfied if it is known to
+    /// be satisfied. This way `or(A, param::<C>())` is satisfied by `A` even if `C` is unbound, while
+    /// `not(param::<C>())` can't be satisfied by a missing binding.
+    ///
+    /// Returns `false` for empty conditions.
+    pub fn evaluate_with_params<'a>(
+        &self,
+        evaluator: impl Fn(VisibilityAttributeId) -> bool,
+        predicates: impl Fn(VisibilityPredicateId) -> bool,
+        params: impl Fn(VisibilityParamId) -> Option<&'a VisibilityCondition>,
+    ) -> bool
     {
         let slice = self.as_slice();
-        if slice.len() == 0 { return false; }
-        evaluate(&evaluator, slice, 0)
+        if slice.is_empty() { return false; }
+        let bound = |param| params(param)
+            .filter(|bound| !bound.has_params())
+            .map(|bound| bound.evaluate_with_predicates(&evaluator, &predicates));
+        evaluate_partial(&evaluator, &predicates, &bound, slice, 0).unwrap_or(false)
     }
 
     /// Extends self with an AND relationship with another visibility condition.
@@ -262,7 +535,7 @@ impl VisibilityCondition
     {
         let pattern = Self::new(pattern);
         let pattern = pattern.as_slice();
-        if pattern.len() == 0 { return 0; }
+        if pattern.is_empty() { return 0; }
 
         self.replace_with(
             pattern.len(),
@@ -356,8 +629,148 @@ impl VisibilityCondition
         self.replace_type::<T>(empty())
     }
 
+    /// Replaces attribute nodes in the current visibility condition using a replacement function.
+    ///
+    /// The function is called on every attribute node. If it returns a condition, then the node is replaced with
+    /// that condition branch.
+    ///
+    /// The final condition will be consolidated (empty nodes removed and expressions simplified).
+    ///
+    /// Returns the number of attribute nodes replaced.
+    ///
+    /// Examples:
+    /**
+    ```rust
+    let mut a = vis!(and(InRoom(1), not(InRoom(2))));
+    a.replace_where(
+        |attr|
+        {
+            if !attr.is::<InRoom>() { return None; }
+            Some(InRoom(attr.inner_id() + 100))
+        }
+    );
+    assert!(a == vis!(and(InRoom(101), not(InRoom(102)))));
+    ```
+    */
+    pub fn replace_where<C: IntoVisibilityCondition>(
+        &mut self,
+        mut replacer: impl FnMut(VisibilityAttributeId) -> Option<C>
+    ) -> usize
+    {
+        self.replace_nodes_where(
+            |node|
+            {
+                let VisibilityConditionNode::Attr(attr) = node else { return None; };
+                (replacer)(attr)
+            }
+        )
+    }
+
+    /// Replaces parameter nodes in the current visibility condition using a replacement function.
+    ///
+    /// The function is called on every parameter node. If it returns a condition, then the node is replaced with
+    /// that condition branch.
+    ///
+    /// The final condition will be consolidated (empty nodes removed and expressions simplified).
+    ///
+    /// Returns the number of parameter nodes replaced.
+    ///
+    /// Examples:
+    /**
+    ```rust
+    let mut a = vis!(and(param::<Zone>(), not(IsSpectator)));
+    a.replace_params(
+        |param|
+        {
+            if param != VisibilityParamId::of::<Zone>() { return None; }
+            Some(InZone(3))
+        }
+    );
+    assert!(a == vis!(and(InZone(3), not(IsSpectator))));
+    ```
+    */
+    pub fn replace_params<C: IntoVisibilityCondition>(
+        &mut self,
+        mut replacer: impl FnMut(VisibilityParamId) -> Option<C>
+    ) -> usize
+    {
+        self.replace_nodes_where(
+            |node|
+            {
+                let VisibilityConditionNode::Param(param) = node else { return None; };
+                (replacer)(param)
+            }
+        )
+    }
+
+    /// Checks if the current visibility condition contains a pattern.
+    ///
+    /// Returns `false` if the input pattern is empty.
+    ///
+    /// Examples:
+    /**
+    ```rust
+    let a = vis!(and(A, or(B, C)));
+    assert!(a.contains(B));
+    assert!(a.contains(or(B, C)));
+    assert!(!a.contains(and(A, B)));
+    ```
+    */
+    pub fn contains(&self, pattern: impl IntoVisibilityCondition) -> bool
+    {
+        let pattern = Self::new(pattern);
+        if pattern.is_empty() { return false; }
+        let pattern = pattern.as_slice();
+        let slice = self.as_slice();
+        if pattern.len() > slice.len() { return false; }
+
+        (0..=(slice.len() - pattern.len()))
+            .any(
+                |start|
+                {
+                    pattern
+                        .iter()
+                        .zip(slice[start..].iter())
+                        .all(|(pattern_node, node)| pattern_node.equivalent(0, node, start))
+                }
+            )
+    }
+
+    /// Iterates the inner ids of attributes of a certain type referenced in the condition tree.
+    ///
+    /// Examples:
+    /**
+    ```rust
+    let a = vis!(or(InRoom(1), and(A, InRoom(2))));
+    assert!(a.attributes_of_type::<InRoom>().eq([1, 2]));
+    ```
+    */
+    pub fn attributes_of_type<T: VisibilityAttribute>(&self) -> impl Iterator<Item = u64> + '_
+    {
+        self.iter_attributes()
+            .filter(|attr| attr.is::<T>())
+            .map(|attr| attr.inner_id())
+    }
+
+    /// Gets the depth of the condition tree.
+    ///
+    /// Empty conditions have a depth of `0`, and conditions with one attribute have a depth of `1`.
+    pub fn depth(&self) -> usize
+    {
+        depth(self.as_slice(), 0)
+    }
+
+    /// Gets the number of nodes in the condition tree.
+    ///
+    /// Empty conditions have `0` nodes.
+    pub fn node_count(&self) -> usize
+    {
+        if self.is_empty() { return 0; }
+        self.as_slice().len()
+    }
+
     /// Makes a new condition from a builder.
-    fn from(builder: VisibilityConditionBuilder) -> Self
+    pub(crate) fn from(builder: VisibilityConditionBuilder) -> Self
     {
         // extract the node tree
         let mut condition = builder.consolidate_and_take();
@@ -372,8 +785,44 @@ impl VisibilityCondition
         }
         else
         {
-            Self::Large(Arc::from(condition.into_vec()))
+            Self::intern(Arc::from(condition.into_vec()))
+        }
+    }
+
+    /// Makes a large condition that shares its allocation with identical large conditions.
+    fn intern(condition: Arc<[VisibilityConditionNode]>) -> Self
+    {
+        let condition = Self::Large(condition);
+        let id = condition.condition_id();
+        let Self::Large(condition) = condition else { unreachable!(); };
+
+        let mut interner = LARGE_CONDITION_INTERNER.lock().unwrap_or_else(|e| e.into_inner());
+        let interner = interner.get_or_insert_with(
+            ||
+            LargeConditionInterner{
+                conditions: HashMap::default(),
+                prune_threshold: MIN_INTERNER_PRUNE_THRESHOLD,
+            }
+        );
+
+        Self::Large(interner.intern(id, condition))
+    }
+
+    /// Replaces root nodes in the current visibility condition using a replacement function.
+    fn replace_nodes_where<C: IntoVisibilityCondition>(
+        &mut self,
+        mut replacer: impl FnMut(VisibilityConditionNode) -> Option<C>
+    ) -> usize
+    {
+        let mut count = 0;
+        let builder = rebuild_with_replacer(&mut replacer, self.as_slice(), 0, VisibilityConditionBuilder::new(), &mut count);
+
+        if count > 0
+        {
+            *self = Self::from(builder);
         }
+
+        count
     }
 
     /// Replaces sections of the existing condition with a replacement condition.
@@ -2,6 +2,7 @@
 use crate::*;
 
 //third-party shortcuts
+use bevy::prelude::Component;
 use smallvec::SmallVec;
 
 //standard shortcuts
@@ -86,6 +87,18 @@ impl VisibilityConditionBuilder
         self.nodes.push(VisibilityConditionNode::Attr(attr));
     }
 
+    /// Adds a PREDICATE node to the end of the condition.
+    pub(crate) fn push_predicate_node(&mut self, predicate: VisibilityPredicateId)
+    {
+        self.nodes.push(VisibilityConditionNode::Predicate(predicate));
+    }
+
+    /// Adds a PARAM node to the end of the condition.
+    pub(crate) fn push_param_node(&mut self, param: VisibilityParamId)
+    {
+        self.nodes.push(VisibilityConditionNode::Param(param));
+    }
+
     /// Adds a NOT node to the end of the condition.
     ///
     /// Assumes the next node to be inserted will be the start of the OR expression's child branch.
@@ -125,7 +138,7 @@ impl VisibilityConditionBuilder
     /// of the branch being inserted to equal the position of the first node in that branch.
     pub(crate) fn push_branch(&mut self, root: usize, branch: &[VisibilityConditionNode])
     {
-        if branch.len() == 0 { return; }
+        if branch.is_empty() { return; }
 
         self.nodes.reserve(branch.len());
         let len = self.nodes.len();
@@ -134,9 +147,11 @@ impl VisibilityConditionBuilder
         {
             match &mut node
             {
-                VisibilityConditionNode::Empty   => { self.num_empty += 1; },
-                VisibilityConditionNode::Attr(_) => (),
-                VisibilityConditionNode::Not     => (),
+                VisibilityConditionNode::Empty        => { self.num_empty += 1; },
+                VisibilityConditionNode::Attr(_)      => (),
+                VisibilityConditionNode::Predicate(_) => (),
+                VisibilityConditionNode::Param(_)     => (),
+                VisibilityConditionNode::Not          => (),
                 VisibilityConditionNode::And(b)  |
                 VisibilityConditionNode::Or(b)   => { *b -= root; *b += len; }
             }
@@ -156,9 +171,11 @@ impl VisibilityConditionBuilder
         {
             match node
             {
-                VisibilityConditionNode::Empty   => (),
-                VisibilityConditionNode::Attr(_) => (),
-                VisibilityConditionNode::Not     => (),
+                VisibilityConditionNode::Empty        => (),
+                VisibilityConditionNode::Attr(_)      => (),
+                VisibilityConditionNode::Predicate(_) => (),
+                VisibilityConditionNode::Param(_)     => (),
+                VisibilityConditionNode::Not          => (),
                 VisibilityConditionNode::And(b)  |
                 VisibilityConditionNode::Or(b)   =>
                 {
@@ -177,7 +194,7 @@ impl VisibilityConditionBuilder
     /// to correctly consolidate, so we need it to have an empty node to avoid broken expressions.
     pub(crate) fn consolidate_and_take(self) -> SmallVec<[VisibilityConditionNode; SMALL_PACK_LEN]>
     {
-        if self.nodes.len() == 0 { return SmallVec::from_slice(&[VisibilityConditionNode::Empty]); }
+        if self.nodes.is_empty() { return SmallVec::from_slice(&[VisibilityConditionNode::Empty]); }
         if self.nodes.len() == 1 || self.num_empty == 0 { return self.nodes; }
 
         // set all invalid branches to empty
@@ -189,7 +206,9 @@ impl VisibilityConditionBuilder
             match nodes[current_node]
             {
                 VisibilityConditionNode::Empty   => (true, nodes, 1),
-                VisibilityConditionNode::Attr(_) => (false, nodes, 0),
+                VisibilityConditionNode::Attr(_) |
+                VisibilityConditionNode::Predicate(_) |
+                VisibilityConditionNode::Param(_) => (false, nodes, 0),
                 VisibilityConditionNode::Not     =>
                 {
                     // recurse child branch
@@ -240,9 +259,11 @@ impl VisibilityConditionBuilder
         {
             match &mut nodes[idx]
             {
-                VisibilityConditionNode::Empty   => { empty_count += 1; continue; },
-                VisibilityConditionNode::Attr(_) => (),
-                VisibilityConditionNode::Not     => (),
+                VisibilityConditionNode::Empty        => { empty_count += 1; continue; },
+                VisibilityConditionNode::Attr(_)      => (),
+                VisibilityConditionNode::Predicate(_) => (),
+                VisibilityConditionNode::Param(_)     => (),
+                VisibilityConditionNode::Not          => (),
                 // note: we incorporated left-branch empty slots within the recursion
                 VisibilityConditionNode::And(b)  |
                 VisibilityConditionNode::Or(b)   => { *b -= empty_count; }
@@ -375,6 +396,57 @@ where
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Creates a PREDICATE visibility condition.
+///
+/// The predicate `T` must be registered with
+/// [`register_visibility_predicate`](VisibilityPredicateAppExt::register_visibility_predicate).
+///
+/**
+```rust
+struct WithinRange;
+
+let condition = VisibilityCondition::new(and(InZone(1), predicate::<WithinRange>()));
+``` 
+*/
+pub fn predicate<T: 'static>() -> impl IntoVisibilityCondition
+{
+    VisibilityConditionWrapper::from(
+        |mut builder: VisibilityConditionBuilder| -> VisibilityConditionBuilder
+        {
+            builder.push_predicate_node(VisibilityPredicateId::of::<T>());
+            builder
+        }
+    )
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Creates a PARAM visibility condition.
+///
+/// The parameter is bound separately for each entity from the entity's `C` component. The component `C` must be
+/// registered with [`register_condition_param`](VisibilityParamsAppExt::register_condition_param).
+///
+/**
+```rust
+#[derive(Component)]
+struct Zone(u64);
+
+let condition = VisibilityCondition::new(and(param::<Zone>(), not(IsSpectator)));
+```
+*/
+pub fn param<C: Component>() -> impl IntoVisibilityCondition
+{
+    VisibilityConditionWrapper::from(
+        |mut builder: VisibilityConditionBuilder| -> VisibilityConditionBuilder
+        {
+            builder.push_param_node(VisibilityParamId::of::<C>());
+            builder
+        }
+    )
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Syntax sugar for `and(A, and(B, C))` etc.
 #[macro_export] macro_rules! all
 {
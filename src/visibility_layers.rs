@@ -0,0 +1,276 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::ecs::entity::EntityHashSet;
+use bevy::prelude::*;
+
+//standard shortcuts
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Marks entities whose [`VisibilityCondition`] was combined from [`VisibilityLayers`](VisibilityLayer).
+#[derive(Component)]
+struct VisibilityLayered;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets a layer's condition from an entity.
+type LayerAccessor = for<'w> fn(&EntityRef<'w>) -> Option<&'w VisibilityCondition>;
+
+/// Registered layers and entities that need their layers re-combined.
+#[derive(Resource, Default)]
+struct VisibilityLayerRegistry
+{
+    /// [ (layer type, layer condition accessor) ] in order of registration.
+    layers: Vec<(TypeId, LayerAccessor)>,
+    dirty: EntityHashSet,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn get_layer_condition<'w, L: Send + Sync + 'static>(entity: &EntityRef<'w>) -> Option<&'w VisibilityCondition>
+{
+    entity.get::<VisibilityLayer<L>>().map(|layer| &layer.condition)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn mark_changed_layers<L: Send + Sync + 'static>(
+    mut registry: ResMut<VisibilityLayerRegistry>,
+    changed: Query<Entity, Changed<VisibilityLayer<L>>>,
+){
+    registry.dirty.extend(changed.iter());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn mark_removed_layer<L: Send + Sync + 'static>(
+    event: Trigger<OnRemove, VisibilityLayer<L>>,
+    mut registry: ResMut<VisibilityLayerRegistry>,
+){
+    registry.dirty.insert(event.target());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn mark_all_on_policy_change(
+    policy: Res<VisibilityLayerPolicy>,
+    mut registry: ResMut<VisibilityLayerRegistry>,
+    layered: Query<Entity, With<VisibilityLayered>>,
+){
+    if !policy.is_changed() { return; }
+    registry.dirty.extend(layered.iter());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn combine_visibility_layers(
+    mut c: Commands,
+    policy: Res<VisibilityLayerPolicy>,
+    mut registry: ResMut<VisibilityLayerRegistry>,
+    entities: Query<EntityRef>,
+){
+    let mut dirty = std::mem::take(&mut registry.dirty);
+
+    for entity in dirty.drain()
+    {
+        let Ok(entity_ref) = entities.get(entity) else { continue; };
+        let layers: Vec<&VisibilityCondition> = registry.layers
+            .iter()
+            .filter_map(|(_, get)| get(&entity_ref))
+            .collect();
+
+        // Clean up entities that no longer have layers.
+        if layers.is_empty()
+        {
+            if entity_ref.contains::<VisibilityLayered>()
+            {
+                c.entity(entity).remove::<(VisibilityCondition, VisibilityLayered)>();
+            }
+            continue;
+        }
+
+        let combined = policy.combine(&layers);
+        if entity_ref.get::<VisibilityCondition>() == Some(&combined) && entity_ref.contains::<VisibilityLayered>()
+        { continue; }
+        c.entity(entity).insert((combined, VisibilityLayered));
+    }
+
+    // Reuse the allocation.
+    registry.dirty = dirty;
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component that contributes one layer to an entity's [`VisibilityCondition`].
+///
+/// Layers are keyed by a marker type `L`, so independent systems can each control their own layer without
+/// overwriting each other. All registered layers on an entity are combined into the entity's [`VisibilityCondition`]
+/// using the [`VisibilityLayerPolicy`].
+///
+/// Layer types must be registered with [`VisibilityLayersAppExt::register_visibility_layer`].
+///
+/// Example:
+/**
+```rust
+struct Stealth;
+struct Ownership;
+
+app.register_visibility_layer::<Stealth>()
+    .register_visibility_layer::<Ownership>();
+
+fn spawn_unit(mut c: Commands, owner: u64)
+{
+    c.spawn((
+        Replicated,
+        VisibilityLayer::<Stealth>::new(vis!(InZone(1))),
+        VisibilityLayer::<Ownership>::new(vis!(or(Client(owner), IsAdmin))),
+    ));
+}
+```
+*/
+#[derive(Component, Debug)]
+pub struct VisibilityLayer<L: Send + Sync + 'static>
+{
+    condition: VisibilityCondition,
+    _phantom: PhantomData<fn() -> L>,
+}
+
+impl<L: Send + Sync + 'static> VisibilityLayer<L>
+{
+    /// Makes a new layer.
+    pub fn new(condition: impl IntoVisibilityCondition) -> Self
+    {
+        Self{ condition: VisibilityCondition::new(condition), _phantom: PhantomData }
+    }
+
+    /// Gets the layer's condition.
+    pub fn condition(&self) -> &VisibilityCondition
+    {
+        &self.condition
+    }
+
+    /// Sets the layer's condition.
+    pub fn set(&mut self, condition: impl IntoVisibilityCondition)
+    {
+        self.condition = VisibilityCondition::new(condition);
+    }
+}
+
+impl<L: Send + Sync + 'static> Clone for VisibilityLayer<L>
+{
+    fn clone(&self) -> Self
+    {
+        Self{ condition: self.condition.clone(), _phantom: PhantomData }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// System set in `PostUpdate` that combines [`VisibilityLayers`](VisibilityLayer) into
+/// [`VisibilityConditions`](VisibilityCondition).
+///
+/// Runs before [`VisibilityUpdateSet`]. Systems that update layers in `PostUpdate` should run before this set so their
+/// changes are applied in the same tick.
+#[derive(SystemSet, Debug, Eq, PartialEq, Clone, Hash)]
+pub struct VisibilityLayerSet;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resource that controls how [`VisibilityLayers`](VisibilityLayer) are combined.
+///
+/// Changing the policy re-combines all layered entities.
+#[derive(Resource, Debug, Copy, Clone, Default)]
+pub enum VisibilityLayerPolicy
+{
+    /// Entities are visible to clients that satisfy all layers.
+    ///
+    /// If any layer is empty, then the combined condition is empty.
+    #[default]
+    And,
+    /// Entities are visible to clients that satisfy at least one layer.
+    Or,
+    /// Layers are combined with a custom function.
+    ///
+    /// Layers are passed in order of registration.
+    Custom(fn(&[&VisibilityCondition]) -> VisibilityCondition),
+}
+
+impl VisibilityLayerPolicy
+{
+    /// Combines layer conditions.
+    pub fn combine(&self, layers: &[&VisibilityCondition]) -> VisibilityCondition
+    {
+        match self
+        {
+            Self::And =>
+            {
+                if layers.iter().any(|layer| layer.is_empty()) { return VisibilityCondition::empty(); }
+                let mut combined = VisibilityCondition::empty();
+                for layer in layers { combined.and((*layer).clone()); }
+                combined
+            }
+            Self::Or =>
+            {
+                let mut combined = VisibilityCondition::empty();
+                for layer in layers { combined.or((*layer).clone()); }
+                combined
+            }
+            Self::Custom(combine) => (combine)(layers),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extends `App` with methods for using [`VisibilityLayers`](VisibilityLayer).
+pub trait VisibilityLayersAppExt
+{
+    /// Registers a [`VisibilityLayer`] type.
+    ///
+    /// Layers are combined into [`VisibilityConditions`](VisibilityCondition) in `PostUpdate` in
+    /// [`VisibilityLayerSet`]. The combined condition overwrites any condition inserted manually, so you should not
+    /// manually insert conditions on entities with layers. The condition is removed when the last layer is removed.
+    fn register_visibility_layer<L: Send + Sync + 'static>(&mut self) -> &mut Self;
+}
+
+impl VisibilityLayersAppExt for App
+{
+    fn register_visibility_layer<L: Send + Sync + 'static>(&mut self) -> &mut Self
+    {
+        if !self.world().contains_resource::<VisibilityLayerRegistry>()
+        {
+            self.init_resource::<VisibilityLayerRegistry>()
+                .init_resource::<VisibilityLayerPolicy>()
+                .configure_sets(PostUpdate, VisibilityLayerSet.before(VisibilityUpdateSet))
+                .add_systems(PostUpdate,
+                    (
+                        mark_all_on_policy_change,
+                        combine_visibility_layers,
+                    )
+                        .chain()
+                        .in_set(VisibilityLayerSet)
+                );
+        }
+
+        let mut registry = self.world_mut().resource_mut::<VisibilityLayerRegistry>();
+        if registry.layers.iter().any(|(type_id, _)| *type_id == TypeId::of::<L>()) { return self; }
+        registry.layers.push((TypeId::of::<L>(), get_layer_condition::<L>));
+
+        self.add_observer(mark_removed_layer::<L>)
+            .add_systems(PostUpdate,
+                mark_changed_layers::<L>
+                    .in_set(VisibilityLayerSet)
+                    .before(combine_visibility_layers)
+            )
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,78 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+
+//standard shortcuts
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn insert_visibility_linger(
+    event: Trigger<OnInsert, VisibilityLinger>,
+    mut visibility_cache: ResMut<VisibilityCache>,
+    lingers: Query<&VisibilityLinger>,
+){
+    let entity = event.target();
+    let Ok(linger) = lingers.get(entity) else { return; };
+    visibility_cache.set_entity_linger(entity, Some(*linger));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn replace_visibility_linger(event: Trigger<OnReplace, VisibilityLinger>, mut visibility_cache: ResMut<VisibilityCache>)
+{
+    visibility_cache.set_entity_linger(event.target(), None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Delays hiding an entity from a client after the entity's [`VisibilityCondition`] stops being satisfied.
+///
+/// If the condition becomes satisfied again before the linger expires, the entity stays visible and the client never
+/// sees it despawn. This prevents entities from flickering in and out of existence on clients whose attributes are
+/// changing rapidly (e.g. when a player is moving back and forth across a zone border).
+///
+/// Insert this as a component on an entity to set the entity's linger, or use [`VisibilitySettings::with_linger`] to
+/// apply a linger to all entities without this component.
+///
+/// Entities forced hidden with [`ClientAttributes::force_hidden`] are hidden immediately.
+///
+/// Example:
+/**
+```rust
+fn spawn_zone_npc(mut c: Commands)
+{
+    c.spawn((Replicated, Npc, vis!(InZone(1)), VisibilityLinger::Ticks(30)));
+}
+```
+*/
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum VisibilityLinger
+{
+    /// Hides the entity after `bevy_replicon`'s server tick has advanced this many times.
+    Ticks(u32),
+    /// Hides the entity after this much time has elapsed according to the `Time` resource.
+    Duration(Duration),
+}
+
+impl VisibilityLinger
+{
+    /// Gets when a lingering entity should be hidden.
+    ///
+    /// The deadline is relative to when lingering entities are next updated, which is after `bevy_replicon` increments
+    /// the server tick.
+    pub(crate) fn deadline(&self) -> ScheduledAt
+    {
+        match self
+        {
+            Self::Ticks(ticks)       => ScheduledAt::AfterTicks(*ticks),
+            Self::Duration(duration) => ScheduledAt::After(*duration),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,142 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_replicon::prelude::ClientVisibility;
+use bevy_replicon::shared::backend::connected_client::NetworkIdMap;
+
+//standard shortcuts
+use std::any::TypeId;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extracts parameter bindings from component `C`.
+#[derive(Resource)]
+struct ConditionParamExtractor<C: Component>
+{
+    extract: Box<dyn Fn(&C) -> VisibilityCondition + Send + Sync + 'static>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn update_condition_param<C: Component>(
+    extractor: Res<ConditionParamExtractor<C>>,
+    id_map: Res<NetworkIdMap>,
+    mut visibility_cache: ResMut<VisibilityCache>,
+    mut client_entities: Query<&mut ClientVisibility>,
+    changed: Query<(Entity, &C), Changed<C>>,
+    all: Query<(Entity, &C)>,
+){
+    let param = VisibilityParamId::of::<C>();
+    let mut bind = |entity: Entity, component: &C|
+    {
+        let binding = (extractor.extract)(component);
+        visibility_cache.set_entity_binding(&id_map, &mut client_entities, entity, param, Some(binding));
+    };
+
+    // Rebind all entities if the extractor was replaced.
+    match extractor.is_changed()
+    {
+        true  => all.iter().for_each(|(entity, component)| bind(entity, component)),
+        false => changed.iter().for_each(|(entity, component)| bind(entity, component)),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Runs when `C` is removed or its entity is despawned.
+fn remove_condition_param<C: Component>(
+    event: Trigger<OnRemove, C>,
+    id_map: Res<NetworkIdMap>,
+    mut visibility_cache: ResMut<VisibilityCache>,
+    mut client_entities: Query<&mut ClientVisibility>,
+){
+    let param = VisibilityParamId::of::<C>();
+    visibility_cache.set_entity_binding(&id_map, &mut client_entities, event.target(), param, None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Id of a visibility condition parameter.
+///
+/// See [`VisibilityParamsAppExt::register_condition_param`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct VisibilityParamId(TypeId);
+
+impl VisibilityParamId
+{
+    /// Gets the id of the parameter bound from component `C`.
+    pub fn of<C: Component>() -> Self
+    {
+        Self(TypeId::of::<C>())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extends `App` with methods for binding visibility condition parameters.
+pub trait VisibilityParamsAppExt
+{
+    /// Registers component `C` as the source of [`param::<C>()`](param) in visibility conditions.
+    ///
+    /// When an entity's [`VisibilityCondition`] contains `param::<C>()`, the parameter evaluates the condition returned
+    /// by `extract` for the entity's `C` component. Parameters are bound per entity when visibility is evaluated, so
+    /// all entities with the same condition template share one cached condition.
+    ///
+    /// Changes to `C` are detected in `PostUpdate` before [`VisibilityUpdateSet`], and only re-evaluate the entity
+    /// whose component changed. If an entity doesn't have `C`, then the parameter is unknown, so it can't satisfy the
+    /// entity's condition even if negated with `not` (see [`VisibilityCondition::evaluate_with_params`]).
+    ///
+    /// Bindings come from the components of the entity that owns the condition. Entities that follow another entity
+    /// with [`VisibleWith`] or [`InheritVisibility`] follow the other entity's bound visibility.
+    ///
+    /// Calling this again with the same `C` replaces the previous `extract` callback and rebinds all entities.
+    ///
+    /// Example:
+    /**
+    ```rust
+    #[derive(Component)]
+    struct Zone(u64);
+
+    #[derive(Component)]
+    struct Team(u64);
+
+    app.register_condition_param::<Zone>(|zone| vis!(InZone(zone.0)))
+        .register_condition_param::<Team>(|team| vis!(InTeam(team.0)));
+
+    fn spawn_loot(mut c: Commands, zone: u64, team: u64)
+    {
+        c.spawn((Replicated, Loot, Zone(zone), Team(team), vis!(and(param::<Zone>(), param::<Team>()))));
+    }
+    ```
+    */
+    fn register_condition_param<C: Component>(
+        &mut self,
+        extract: impl Fn(&C) -> VisibilityCondition + Send + Sync + 'static
+    ) -> &mut Self;
+}
+
+impl VisibilityParamsAppExt for App
+{
+    fn register_condition_param<C: Component>(
+        &mut self,
+        extract: impl Fn(&C) -> VisibilityCondition + Send + Sync + 'static
+    ) -> &mut Self
+    {
+        if let Some(mut extractor) = self.world_mut().get_resource_mut::<ConditionParamExtractor<C>>()
+        {
+            extractor.extract = Box::new(extract);
+            return self;
+        }
+
+        self.insert_resource(ConditionParamExtractor::<C>{ extract: Box::new(extract) })
+            .add_observer(remove_condition_param::<C>)
+            .add_systems(PostUpdate, update_condition_param::<C>.before(VisibilityUpdateSet))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
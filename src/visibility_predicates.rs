@@ -0,0 +1,251 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use bevy_replicon::prelude::ClientVisibility;
+use bevy_replicon::server::server_tick::ServerTick;
+use bevy_replicon::shared::backend::connected_client::{NetworkId, NetworkIdMap};
+use bevy_replicon::shared::replicon_tick::RepliconTick;
+
+//standard shortcuts
+use std::any::{type_name, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+type PredicateSystem = Box<dyn System<In = In<Vec<(u64, Entity)>>, Out = Vec<bool>>>;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+struct RegisteredPredicate
+{
+    cadence: PredicateCadence,
+    /// When the predicate should next be evaluated for all clients and entities.
+    next: Option<ScheduledAt>,
+    system: PredicateSystem,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Registered visibility predicates.
+#[derive(Resource, Default)]
+pub(crate) struct VisibilityPredicates
+{
+    predicates: HashMap<VisibilityPredicateId, RegisteredPredicate>,
+}
+
+impl VisibilityPredicates
+{
+    /// Collects predicates that are due for evaluation, and schedules their next evaluation.
+    fn take_due(&mut self, elapsed: Duration, tick: RepliconTick) -> HashSet<VisibilityPredicateId>
+    {
+        let mut due = HashSet::default();
+        for (id, predicate) in self.predicates.iter_mut()
+        {
+            if predicate.next.is_some_and(|next| !next.is_due(elapsed, tick)) { continue; }
+            predicate.next = Some(predicate.cadence.next(elapsed, tick));
+            due.insert(*id);
+        }
+        due
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn has_visibility_predicates(predicates: Res<VisibilityPredicates>) -> bool
+{
+    !predicates.predicates.is_empty()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Evaluates predicates that are due, and predicates that have not been evaluated for a client and entity.
+pub(crate) fn evaluate_visibility_predicates(
+    world: &mut World,
+    state: &mut SystemState<(Res<NetworkIdMap>, ResMut<VisibilityCache>, Query<&mut ClientVisibility>)>,
+){
+    let elapsed = world.resource::<Time>().elapsed();
+    let tick = **world.resource::<ServerTick>();
+
+    world.resource_scope(|world, mut predicates: Mut<VisibilityPredicates>| {
+        // Collect work.
+        // - Skip collecting work if no predicates are due and all clients and entities have results.
+        let due = predicates.take_due(elapsed, tick);
+        let unevaluated = world.resource_mut::<VisibilityCache>().take_unevaluated_predicates();
+        if due.is_empty() && !unevaluated { return; }
+        let id_map = world.resource::<NetworkIdMap>();
+        let work = world.resource::<VisibilityCache>().predicate_work(
+            |client_id| id_map.contains_key(&NetworkId::new(client_id)),
+            |predicate, has_result| {
+                predicates.predicates.contains_key(&predicate) && (!has_result || due.contains(&predicate))
+            },
+        );
+        if work.is_empty() { return; }
+
+        // Evaluate predicates, running each predicate once for all of its clients and entities.
+        let mut batches = HashMap::<VisibilityPredicateId, Vec<(u64, Entity)>>::default();
+        for (client_id, entity, predicate) in work
+        {
+            batches.entry(predicate).or_default().push((client_id, entity));
+        }
+
+        let mut results = Vec::default();
+        for (predicate, pairs) in batches
+        {
+            let system = &mut predicates.predicates.get_mut(&predicate).unwrap().system;
+            let outputs = system.run(pairs.clone(), world);
+            if outputs.len() != pairs.len()
+            {
+                tracing::error!(?predicate, "visibility predicate returned {} results for {} inputs, missing results \
+                    are treated as false", outputs.len(), pairs.len());
+            }
+            let outputs = outputs.into_iter().chain(std::iter::repeat(false));
+            results.extend(pairs.into_iter().zip(outputs).map(|((client_id, entity), r)| (client_id, entity, predicate, r)));
+        }
+
+        // Apply results.
+        let (id_map, mut cache, mut client_entities) = state.get_mut(world);
+        cache.apply_predicate_results(&id_map, &mut client_entities, results);
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Id of a visibility predicate.
+///
+/// See [`VisibilityPredicateAppExt::register_visibility_predicate`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct VisibilityPredicateId(TypeId);
+
+impl VisibilityPredicateId
+{
+    /// Gets the id of predicate `T`.
+    pub fn of<T: 'static>() -> Self
+    {
+        Self(TypeId::of::<T>())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// How often a visibility predicate is re-evaluated.
+///
+/// Predicates are always evaluated for new clients and entities as soon as possible, regardless of cadence.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PredicateCadence
+{
+    /// Re-evaluate after `bevy_replicon`'s server tick has advanced this many times.
+    ///
+    /// Values less than `1` are treated as `1`.
+    Ticks(u32),
+    /// Re-evaluate after this much time has elapsed according to the `Time` resource.
+    Duration(Duration),
+}
+
+impl PredicateCadence
+{
+    fn next(&self, elapsed: Duration, tick: RepliconTick) -> ScheduledAt
+    {
+        match self
+        {
+            Self::Ticks(ticks)       => ScheduledAt::Tick(tick + (*ticks).max(1)),
+            Self::Duration(duration) => ScheduledAt::Elapsed(elapsed + *duration),
+        }
+    }
+}
+
+impl Default for PredicateCadence
+{
+    fn default() -> Self
+    {
+        Self::Ticks(1)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extends `App` with methods for registering visibility predicates.
+pub trait VisibilityPredicateAppExt
+{
+    /// Registers a predicate that is evaluated for each client and each entity whose [`VisibilityCondition`]
+    /// references [`predicate::<T>()`](predicate).
+    ///
+    /// The predicate is a read-only system that receives a batch of (client id, entity) pairs, and returns whether
+    /// the predicate is satisfied for each pair in the same order. Missing results are treated as `false`.
+    /// Predicates can be combined with attributes using `and`/`or`/`not`, which allows conditions that depend on both
+    /// the client and the entity (e.g. distance to the client's avatar).
+    ///
+    /// Predicates are evaluated in [`VisibilityUpdateSet`] at the given cadence. Between evaluations, the last result
+    /// is used when the client's attributes or the entity's condition change.
+    ///
+    /// Registering `T` again replaces its predicate, which is then re-evaluated for all clients and entities.
+    ///
+    /// Example:
+    /**
+    ```rust
+    struct WithinRange;
+
+    app.register_visibility_predicate::<WithinRange, _>(
+        PredicateCadence::Ticks(10),
+        |
+            In(pairs): In<Vec<(u64, Entity)>>,
+            avatars: Query<(&Transform, &Avatar)>,
+            transforms: Query<&Transform>,
+        | -> Vec<bool>
+        {
+            pairs
+                .into_iter()
+                .map(|(client_id, entity)| {
+                    let Ok(transform) = transforms.get(entity) else { return false; };
+                    avatars
+                        .iter()
+                        .filter(|(_, avatar)| avatar.0 == client_id)
+                        .any(|(avatar, _)| avatar.translation.distance(transform.translation) <= 50.0)
+                })
+                .collect()
+        }
+    );
+
+    fn spawn_treasure(mut c: Commands)
+    {
+        c.spawn((Replicated, Treasure, vis!(and(InZone(1), predicate::<WithinRange>()))));
+    }
+    ```
+    */
+    fn register_visibility_predicate<T: 'static, M>(
+        &mut self,
+        cadence: PredicateCadence,
+        predicate: impl IntoSystem<In<Vec<(u64, Entity)>>, Vec<bool>, M, System: ReadOnlySystem>,
+    ) -> &mut Self;
+}
+
+impl VisibilityPredicateAppExt for App
+{
+    fn register_visibility_predicate<T: 'static, M>(
+        &mut self,
+        cadence: PredicateCadence,
+        predicate: impl IntoSystem<In<Vec<(u64, Entity)>>, Vec<bool>, M, System: ReadOnlySystem>,
+    ) -> &mut Self
+    {
+        let mut system = IntoSystem::into_system(predicate);
+        system.initialize(self.world_mut());
+
+        // A replaced predicate has no scheduled evaluation, so it is re-evaluated on the next update.
+        if self.world_mut()
+            .get_resource_or_init::<VisibilityPredicates>()
+            .predicates
+            .insert(VisibilityPredicateId::of::<T>(), RegisteredPredicate{ cadence, next: None, system: Box::new(system) })
+            .is_some()
+        { tracing::debug!("replaced visibility predicate {}", type_name::<T>()); }
+
+        self
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
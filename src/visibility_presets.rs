@@ -0,0 +1,189 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::ecs::entity::EntityHashSet;
+use bevy::prelude::*;
+
+//standard shortcuts
+use std::collections::{HashMap, HashSet};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn insert_visibility_preset(
+    event: Trigger<OnInsert, VisibilityPreset>,
+    mut c: Commands,
+    mut presets: ResMut<VisibilityPresets>,
+    entities: Query<&VisibilityPreset>,
+){
+    let entity = event.target();
+    let Ok(preset) = entities.get(entity) else { return; };
+
+    // Entities that reference unknown presets are updated when the preset is defined.
+    presets.users.entry(preset.0).or_default().insert(entity);
+    let Some(condition) = presets.get(preset.0).cloned()
+    else
+    {
+        tracing::warn!(?entity, ?preset, "visibility preset is unknown, waiting for it to be defined");
+        c.entity(entity).try_remove::<VisibilityLayer<VisibilityPreset>>();
+        return;
+    };
+
+    c.entity(entity).insert(VisibilityLayer::<VisibilityPreset>::new(condition));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn replace_visibility_preset(
+    event: Trigger<OnReplace, VisibilityPreset>,
+    mut presets: ResMut<VisibilityPresets>,
+    entities: Query<&VisibilityPreset>,
+){
+    let entity = event.target();
+    let Ok(preset) = entities.get(entity) else { return; };
+    let Some(users) = presets.users.get_mut(&preset.0) else { return; };
+    users.remove(&entity);
+    if users.is_empty() { presets.users.remove(&preset.0); }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn remove_visibility_preset(event: Trigger<OnRemove, VisibilityPreset>, mut c: Commands)
+{
+    c.entity(event.target()).try_remove::<VisibilityLayer<VisibilityPreset>>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Updates the layers of entities that use redefined or newly defined presets.
+pub(crate) fn update_visibility_presets(mut c: Commands, mut presets: ResMut<VisibilityPresets>)
+{
+    if presets.dirty.is_empty() { return; }
+
+    for handle in std::mem::take(&mut presets.dirty)
+    {
+        let Some(users) = presets.users.get(&handle) else { continue; };
+        let Some(condition) = presets.get(handle) else { continue; };
+        for entity in users.iter()
+        {
+            c.entity(*entity).try_insert(VisibilityLayer::<VisibilityPreset>::new(condition.clone()));
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Handle to a preset in [`VisibilityPresets`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct VisibilityPresetHandle(usize);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component for entities whose [`VisibilityCondition`] is a preset from [`VisibilityPresets`].
+///
+/// The entity's `VisibilityLayer<VisibilityPreset>` is set to the preset's current condition, and is updated when the
+/// preset is redefined. The layer is combined with the entity's other [`VisibilityLayers`](VisibilityLayer). If the
+/// preset is not defined yet, then the entity has no preset layer until it is defined. The layer is removed when
+/// this component is removed.
+///
+/// Example:
+/**
+```rust
+fn spawn_loot(mut c: Commands, presets: Res<VisibilityPresets>)
+{
+    let Some(preset) = presets.handle("BossRoomLoot") else { return; };
+    c.spawn((Replicated, Loot, VisibilityPreset(preset)));
+}
+```
+*/
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+pub struct VisibilityPreset(pub VisibilityPresetHandle);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Named visibility conditions that can be shared by many entities.
+///
+/// Entities reference presets with the [`VisibilityPreset`] component. Defining or redefining a preset updates the
+/// layers of all entities that reference it in `PostUpdate` before [`VisibilityLayerSet`].
+///
+/// Presets may contain [`param`] nodes, which are bound separately for each entity.
+///
+/// Example:
+/**
+```rust
+fn setup_presets(mut presets: ResMut<VisibilityPresets>)
+{
+    presets.define("BossRoomLoot", InRoom(3));
+}
+
+fn hide_from_spectators(mut presets: ResMut<VisibilityPresets>)
+{
+    presets.define("BossRoomLoot", and(InRoom(3), not(IsSpectator)));
+}
+```
+*/
+#[derive(Resource, Debug, Default)]
+pub struct VisibilityPresets
+{
+    /// [ name : handle ]
+    names: HashMap<String, VisibilityPresetHandle>,
+    /// Preset conditions indexed by handle.
+    conditions: Vec<VisibilityCondition>,
+    /// [ handle : [ entity ] ]
+    /// Includes entities that reference presets that are not defined yet.
+    users: HashMap<VisibilityPresetHandle, EntityHashSet>,
+    /// Presets that were redefined since their users were last updated.
+    dirty: HashSet<VisibilityPresetHandle>,
+}
+
+impl VisibilityPresets
+{
+    /// Defines a preset, or redefines it if the name is already in use.
+    ///
+    /// Returns the preset's handle, which doesn't change when the preset is redefined.
+    pub fn define(&mut self, name: impl AsRef<str>, condition: impl IntoVisibilityCondition) -> VisibilityPresetHandle
+    {
+        let condition = VisibilityCondition::new(condition);
+
+        let Some(handle) = self.names.get(name.as_ref()).copied()
+        else
+        {
+            let handle = VisibilityPresetHandle(self.conditions.len());
+            self.names.insert(String::from(name.as_ref()), handle);
+            self.conditions.push(condition);
+            if self.users.contains_key(&handle) { self.dirty.insert(handle); }
+            return handle;
+        };
+
+        if self.conditions[handle.0] != condition
+        {
+            self.conditions[handle.0] = condition;
+            self.dirty.insert(handle);
+        }
+        handle
+    }
+
+    /// Gets the handle of a preset.
+    pub fn handle(&self, name: impl AsRef<str>) -> Option<VisibilityPresetHandle>
+    {
+        self.names.get(name.as_ref()).copied()
+    }
+
+    /// Gets the current condition of a preset.
+    pub fn get(&self, handle: VisibilityPresetHandle) -> Option<&VisibilityCondition>
+    {
+        self.conditions.get(handle.0)
+    }
+
+    /// Iterates the entities that reference a preset.
+    ///
+    /// Includes entities waiting for the preset to be defined.
+    pub fn users(&self, handle: VisibilityPresetHandle) -> impl Iterator<Item = Entity> + '_
+    {
+        self.users.get(&handle).into_iter().flat_map(|users| users.iter().copied())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
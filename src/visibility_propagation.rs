@@ -0,0 +1,260 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::ecs::entity::EntityHashSet;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_replicon::prelude::ClientVisibility;
+use bevy_replicon::shared::backend::connected_client::NetworkIdMap;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the entity whose visibility an entity follows.
+///
+/// [`VisibleWith`] takes precedence over [`InheritVisibility`].
+fn link_anchor(visible_with: Option<&VisibleWith>, child_of: Option<&ChildOf>) -> Option<Entity>
+{
+    if let Some(visible_with) = visible_with { return Some(visible_with.anchor()); }
+    child_of.map(|c| c.parent())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+type LinkedQueryData = (Option<&'static VisibleWith>, Option<&'static ChildOf>, Option<&'static InheritVisibility>);
+type LinkedFilter = Or<(With<VisibleWith>, With<InheritVisibility>)>;
+type ChangedLinkFilter = (
+    LinkedFilter,
+    Or<(Changed<VisibleWith>, Changed<InheritVisibility>, Changed<ChildOf>)>,
+);
+
+/// Entities that may need their links updated.
+#[derive(SystemParam)]
+pub(crate) struct VisibilityLinkChanges<'w, 's>
+{
+    changed: Query<'w, 's, Entity, ChangedLinkFilter>,
+    removed_parents: RemovedComponents<'w, 's, ChildOf>,
+    removed_inherit: RemovedComponents<'w, 's, InheritVisibility>,
+    removed_visible_with: RemovedComponents<'w, 's, VisibleWith>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Collects entities that link to the entity.
+fn extend_dependents(
+    dirty: &mut Vec<Entity>,
+    entity: Entity,
+    children: &Query<&Children>,
+    dependents: &Query<&VisibleWithDependents>,
+){
+    if let Ok(entity_children) = children.get(entity) { dirty.extend(entity_children.iter()); }
+    if let Ok(entity_dependents) = dependents.get(entity) { dirty.extend(entity_dependents.iter()); }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Checks if following the links from an entity leads back to the entity.
+fn is_in_link_cycle(entity: Entity, linked: &Query<LinkedQueryData, LinkedFilter>) -> bool
+{
+    let mut visited = EntityHashSet::default();
+    let mut current = entity;
+    while let Ok((visible_with, child_of, _)) = linked.get(current)
+    {
+        let Some(anchor) = link_anchor(visible_with, child_of) else { return false; };
+        if anchor == entity { return true; }
+        if !visited.insert(anchor) { return false; }
+        current = anchor;
+    }
+    false
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Writes the extra condition of [`InheritVisibility`] to the entity's layer.
+pub(crate) fn insert_inherit_visibility(
+    event: Trigger<OnInsert, InheritVisibility>,
+    mut c: Commands,
+    inherit: Query<&InheritVisibility>,
+){
+    let entity = event.target();
+    let Ok(inherit) = inherit.get(entity) else { return; };
+    match inherit.condition()
+    {
+        Some(condition) => { c.entity(entity).insert(VisibilityLayer::<InheritVisibility>::new(condition.clone())); }
+        None            => { c.entity(entity).try_remove::<VisibilityLayer<InheritVisibility>>(); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn remove_inherit_visibility(event: Trigger<OnRemove, InheritVisibility>, mut c: Commands)
+{
+    c.entity(event.target()).try_remove::<VisibilityLayer<InheritVisibility>>();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Updates the visibility links of entities with [`InheritVisibility`] or [`VisibleWith`].
+///
+/// Entities that follow updated entities are also updated, since they may have joined or left a link cycle.
+pub(crate) fn update_visibility_links(
+    id_map: Res<NetworkIdMap>,
+    mut visibility_cache: ResMut<VisibilityCache>,
+    mut client_entities: Query<&mut ClientVisibility>,
+    mut changes: VisibilityLinkChanges,
+    children: Query<&Children>,
+    dependents: Query<&VisibleWithDependents>,
+    linked: Query<LinkedQueryData, LinkedFilter>,
+){
+    // Collect entities that may need updates.
+    let mut dirty: Vec<Entity> = changes.changed.iter().collect();
+    dirty.extend(changes.removed_parents.read());
+    dirty.extend(changes.removed_inherit.read());
+    dirty.extend(changes.removed_visible_with.read());
+    if dirty.is_empty() { return; }
+
+    // Update links.
+    // - Entities that are no longer linked lose their links.
+    let mut visited = EntityHashSet::default();
+    while let Some(entity) = dirty.pop()
+    {
+        if !visited.insert(entity) { continue; }
+
+        let link = linked.get(entity).ok().map(
+            |(visible_with, child_of, inherit)|
+            {
+                if is_in_link_cycle(entity, &linked)
+                {
+                    tracing::warn!(?entity, "entity visibility is linked to itself, hiding it");
+                    return VisibilityLink{ anchor: None, and_own: false };
+                }
+                VisibilityLink{
+                    anchor: link_anchor(visible_with, child_of),
+                    and_own: visible_with.is_none() && inherit.is_some_and(|i| i.condition().is_some()),
+                }
+            }
+        );
+        visibility_cache.set_entity_link(&id_map, &mut client_entities, entity, link);
+
+        extend_dependents(&mut dirty, entity, &children, &dependents);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component that makes an entity visible to the clients that can see its `ChildOf` parent.
+///
+/// The entity follows the parent's output visibility for each client, including the parent's overrides, linger,
+/// reveal budget, and predicates. It is updated whenever the parent's visibility or the entity's parent changes.
+/// Chains of inheriting entities are supported.
+///
+/// With [`Self::and`], the extra condition is written to the entity's `VisibilityLayer<InheritVisibility>`, and
+/// clients must also satisfy the entity's combined [`VisibilityCondition`]. Otherwise the entity's own condition is
+/// ignored. The entity's own [`VisibilityLinger`] and [`VisibilityPriority`] are ignored, but overrides apply.
+///
+/// The entity will be hidden from all clients if it has no parent. Entities in a cycle of links are hidden.
+///
+/// Example:
+/**
+```rust
+fn spawn_player(mut c: Commands, client_id: u64)
+{
+    c.spawn((Replicated, vis!(Global)))
+        .with_children(|parent| {
+            // Visible whenever the player is visible.
+            parent.spawn((Replicated, Weapon, InheritVisibility::new()));
+            // Visible to clients that see the player and are in the player's team.
+            parent.spawn((Replicated, Inventory, InheritVisibility::and(InTeam(1))));
+        });
+}
+```
+*/
+#[derive(Component, Debug, Clone, Default)]
+pub struct InheritVisibility
+{
+    condition: Option<VisibilityCondition>,
+}
+
+impl InheritVisibility
+{
+    /// Makes a component that only follows the parent's visibility.
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Makes a component that AND-s the parent's visibility with an extra condition.
+    pub fn and(condition: impl IntoVisibilityCondition) -> Self
+    {
+        Self{ condition: Some(VisibilityCondition::new(condition)) }
+    }
+
+    /// Gets the extra condition.
+    pub fn condition(&self) -> Option<&VisibilityCondition>
+    {
+        self.condition.as_ref()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Relationship component that makes an entity visible to exactly the clients that can see an anchor entity.
+///
+/// The entity mirrors the anchor's output visibility for each client, including the anchor's overrides, linger,
+/// reveal budget, and predicates, so it is visible to the same clients even as client attributes change. The
+/// entity's own [`VisibilityCondition`], [`VisibilityLinger`], and [`VisibilityPriority`] are ignored, but overrides
+/// apply. Anchors can themselves be linked to other entities with [`VisibleWith`] or [`InheritVisibility`].
+///
+/// If the anchor is despawned then this component is removed, and the entity's visibility returns to its own
+/// condition. Entities in a cycle of links are hidden.
+///
+/// This takes precedence over [`InheritVisibility`] if an entity has both.
+///
+/// Example:
+/**
+```rust
+fn spawn_projectile(mut c: Commands, shooter: Entity)
+{
+    c.spawn((Replicated, Projectile, VisibleWith(shooter)));
+}
+```
+*/
+#[derive(Component, Debug, Copy, Clone, Eq, PartialEq)]
+#[relationship(relationship_target = VisibleWithDependents)]
+pub struct VisibleWith(pub Entity);
+
+impl VisibleWith
+{
+    /// Gets the anchor entity.
+    pub fn anchor(&self) -> Entity
+    {
+        self.0
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Relationship target of [`VisibleWith`].
+///
+/// Tracks the entities whose visibility follows this entity.
+#[derive(Component, Debug, Default, Eq, PartialEq)]
+#[relationship_target(relationship = VisibleWith)]
+pub struct VisibleWithDependents(Vec<Entity>);
+
+impl VisibleWithDependents
+{
+    /// Iterates the dependent entities.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_
+    {
+        self.0.iter().copied()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
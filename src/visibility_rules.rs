@@ -0,0 +1,501 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::ecs::component::ComponentId;
+use bevy::ecs::entity::EntityHashSet;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+//standard shortcuts
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Names that can be referenced by [`VisibilityRules`] assets.
+#[derive(Resource, Default)]
+struct VisibilityRuleRegistry
+{
+    /// [ name : component id ]
+    components: HashMap<String, ComponentId>,
+    /// [ name : attribute id constructor ]
+    attributes: HashMap<String, fn(u64) -> VisibilityAttributeId>,
+    /// [ name : param id ]
+    params: HashMap<String, VisibilityParamId>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Rules compiled from the active [`VisibilityRules`] asset.
+#[derive(Resource, Default)]
+struct CompiledVisibilityRules
+{
+    /// [ (component id, condition) ] in order of precedence.
+    rules: Vec<(ComponentId, VisibilityCondition)>,
+    /// Set when the rules changed and need to be re-applied to all entities.
+    needs_apply: bool,
+}
+
+impl CompiledVisibilityRules
+{
+    /// Finds the first rule that applies to an entity.
+    fn find(&self, entity: &EntityRef) -> Option<&VisibilityCondition>
+    {
+        self.rules
+            .iter()
+            .find(|(component_id, _)| entity.contains_id(*component_id))
+            .map(|(_, condition)| condition)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Writes a rule condition into a condition builder.
+fn build_rule_condition(
+    registry    : &VisibilityRuleRegistry,
+    condition   : &VisibilityRuleCondition,
+    mut builder : VisibilityConditionBuilder,
+) -> Result<VisibilityConditionBuilder, String>
+{
+    match condition
+    {
+        VisibilityRuleCondition::Empty =>
+        {
+            builder.push_empty(0);
+        }
+        VisibilityRuleCondition::Attr(name) =>
+        {
+            let Some(constructor) = registry.attributes.get(name)
+            else { return Err(format!("unknown attribute {name:?}")); };
+            builder.push_attr_node((constructor)(0u64));
+        }
+        VisibilityRuleCondition::AttrId(name, inner_id) =>
+        {
+            let Some(constructor) = registry.attributes.get(name)
+            else { return Err(format!("unknown attribute {name:?}")); };
+            builder.push_attr_node((constructor)(*inner_id));
+        }
+        VisibilityRuleCondition::Param(name) =>
+        {
+            let Some(param) = registry.params.get(name)
+            else { return Err(format!("unknown param {name:?}")); };
+            builder.push_param_node(*param);
+        }
+        VisibilityRuleCondition::Not(a) =>
+        {
+            builder.push_not_node();
+            builder = build_rule_condition(registry, a, builder)?;
+        }
+        VisibilityRuleCondition::And(a, b) =>
+        {
+            let and_node = builder.push_empty(2);
+            builder = build_rule_condition(registry, a, builder)?;
+            builder.set_and_node(and_node);
+            builder = build_rule_condition(registry, b, builder)?;
+        }
+        VisibilityRuleCondition::Or(a, b) =>
+        {
+            let or_node = builder.push_empty(2);
+            builder = build_rule_condition(registry, a, builder)?;
+            builder.set_or_node(or_node);
+            builder = build_rule_condition(registry, b, builder)?;
+        }
+        VisibilityRuleCondition::All(conditions) |
+        VisibilityRuleCondition::Any(conditions) =>
+        {
+            // Equivalent to the `all!()`/`any!()` macros: `and(A, and(B, C))` etc.
+            let is_all = matches!(condition, VisibilityRuleCondition::All(_));
+            let Some((last, rest)) = conditions.split_last() else { builder.push_empty(0); return Ok(builder); };
+
+            for condition in rest.iter()
+            {
+                let node = builder.push_empty(2);
+                builder = build_rule_condition(registry, condition, builder)?;
+                match is_all
+                {
+                    true  => builder.set_and_node(node),
+                    false => builder.set_or_node(node),
+                }
+            }
+            builder = build_rule_condition(registry, last, builder)?;
+        }
+    }
+
+    Ok(builder)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Updates the rules layer of an entity based on the currently compiled rules.
+fn apply_rules_to_entity(world: &mut World, entity: Entity)
+{
+    let Ok(entity_ref) = world.get_entity(entity) else { return; };
+    let rules = world.resource::<CompiledVisibilityRules>();
+
+    match rules.find(&entity_ref)
+    {
+        Some(condition) =>
+        {
+            // Avoid triggering change detection if the condition is unchanged.
+            if entity_ref.get::<VisibilityLayer<VisibilityRules>>().map(|l| l.condition()) == Some(condition)
+            { return; }
+
+            let layer = VisibilityLayer::<VisibilityRules>::new(condition.clone());
+            world.entity_mut(entity).insert(layer);
+        }
+        None =>
+        {
+            if !entity_ref.contains::<VisibilityLayer<VisibilityRules>>() { return; }
+            world.entity_mut(entity).remove::<VisibilityLayer<VisibilityRules>>();
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn queue_apply_rules_to_entity(entity: Entity, c: &mut Commands)
+{
+    c.queue(
+        move |world: &mut World|
+        {
+            // Rules are only applied to new entities after the initial rules application.
+            // - Ignore entities if VisibilityRulesPlugin is missing.
+            let Some(rules) = world.get_resource::<CompiledVisibilityRules>() else { return; };
+            if rules.needs_apply { return; }
+            apply_rules_to_entity(world, entity);
+        }
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn apply_rules_on_insert<T: Component>(event: Trigger<OnInsert, T>, mut c: Commands)
+{
+    queue_apply_rules_to_entity(event.target(), &mut c);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn apply_rules_on_remove<T: Component>(event: Trigger<OnRemove, T>, mut c: Commands)
+{
+    // The command runs after the component is removed.
+    queue_apply_rules_to_entity(event.target(), &mut c);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn compile_visibility_rules(
+    mut events : EventReader<AssetEvent<VisibilityRules>>,
+    handle     : Option<Res<VisibilityRulesHandle>>,
+    assets     : Res<Assets<VisibilityRules>>,
+    registry   : Res<VisibilityRuleRegistry>,
+    mut compiled : ResMut<CompiledVisibilityRules>,
+){
+    let handle_id = handle.as_ref().map(|h| h.0.id());
+    let handle_changed = handle.as_ref().is_some_and(|h| h.is_changed());
+    let asset_changed = events
+        .read()
+        .any(
+            |event|
+            match event
+            {
+                AssetEvent::LoadedWithDependencies{ id } |
+                AssetEvent::Modified{ id }               |
+                AssetEvent::Removed{ id }                => Some(*id) == handle_id,
+                _                                        => false,
+            }
+        );
+    if !handle_changed && !asset_changed { return; }
+
+    // Compile the new rules.
+    // - If the asset is not available then all rules are cleared.
+    compiled.rules.clear();
+    compiled.needs_apply = true;
+
+    let Some(rules) = handle_id.and_then(|id| assets.get(id)) else { return; };
+
+    for rule in rules.rules.iter()
+    {
+        let Some(component_id) = registry.components.get(&rule.component)
+        else { tracing::error!(?rule, "ignoring visibility rule for unregistered component"); continue; };
+
+        match build_rule_condition(&registry, &rule.condition, VisibilityConditionBuilder::new())
+        {
+            Ok(builder) => compiled.rules.push((*component_id, VisibilityCondition::from(builder))),
+            Err(err)    => tracing::error!(?rule, "ignoring invalid visibility rule: {err}"),
+        }
+    }
+
+    tracing::debug!(?handle_id, "compiled {} visibility rules", compiled.rules.len());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn apply_visibility_rules(world: &mut World)
+{
+    if !world.resource::<CompiledVisibilityRules>().needs_apply { return; }
+    world.resource_mut::<CompiledVisibilityRules>().needs_apply = false;
+
+    // Collect entities that may be affected by rules.
+    let mut entities = EntityHashSet::default();
+    let component_ids: Vec<ComponentId> = world.resource::<VisibilityRuleRegistry>()
+        .components
+        .values()
+        .copied()
+        .collect();
+
+    for component_id in component_ids
+    {
+        let mut query = QueryBuilder::<Entity>::new(world).with_id(component_id).build();
+        entities.extend(query.iter(world));
+    }
+    let mut query = world.query_filtered::<Entity, With<VisibilityLayer<VisibilityRules>>>();
+    entities.extend(query.iter(world));
+
+    // Apply rules.
+    for entity in entities
+    {
+        apply_rules_to_entity(world, entity);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A condition expression within a [`VisibilityRule`].
+///
+/// Attributes are referenced by the names registered with
+/// [`VisibilityRulesAppExt::register_visibility_rule_attribute`], and parameters by the names registered with
+/// [`VisibilityRulesAppExt::register_visibility_rule_param`].
+///
+/// Example (RON): `And(Param("Zone"), Not(Attr("IsDead")))`
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum VisibilityRuleCondition
+{
+    /// Equivalent to [`empty()`].
+    Empty,
+    /// An attribute with inner id `0` (e.g. attributes that derive [`VisibilityAttribute`]).
+    Attr(String),
+    /// An attribute with an explicit inner id (see [`VisibilityAttribute::inner_attribute_id`]).
+    ///
+    /// The inner id is used as-is. Derived attributes with fields only have predictable inner ids if they are
+    /// `#[visibility_attribute(packed)]`, since other derived attributes hash their fields.
+    AttrId(String, u64),
+    /// Equivalent to [`param()`].
+    Param(String),
+    /// Equivalent to [`not()`].
+    Not(Box<Self>),
+    /// Equivalent to [`and()`].
+    And(Box<Self>, Box<Self>),
+    /// Equivalent to [`or()`].
+    Or(Box<Self>, Box<Self>),
+    /// Equivalent to [`all!()`](crate::all).
+    All(Vec<Self>),
+    /// Equivalent to [`any!()`](crate::any).
+    Any(Vec<Self>),
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A rule that assigns a [`VisibilityCondition`] to all entities with a specific component.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VisibilityRule
+{
+    /// Name registered with [`VisibilityRulesAppExt::register_visibility_rule_component`].
+    pub component: String,
+    /// The condition to assign.
+    pub condition: VisibilityRuleCondition,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Asset that maps components to visibility conditions.
+///
+/// Rules are applied to entities in order, and the first rule that matches an entity is used. The rule's condition is
+/// written to the entity's `VisibilityLayer<VisibilityRules>`, which is combined with the entity's other
+/// [`VisibilityLayers`](VisibilityLayer). If no rule matches an entity that was previously matched, then its layer
+/// will be removed. Entities that were never matched by a rule are not affected.
+///
+/// Use [`VisibilityRulesHandle`] to select the active rules. Rules will be re-applied when the asset is hot-reloaded.
+///
+/// Example (`loot.vis.ron`):
+/**
+```ron
+(
+    rules: [
+        (component: "Loot", condition: And(AttrId("InZone", 3), Not(Attr("IsDead")))),
+        (component: "Chest", condition: And(Param("Zone"), Not(Attr("IsDead")))),
+        (component: "Npc", condition: Attr("Global")),
+    ]
+)
+```
+*/
+#[derive(Asset, TypePath, Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VisibilityRules
+{
+    /// Rules in order of precedence.
+    pub rules: Vec<VisibilityRule>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resource that selects the active [`VisibilityRules`] asset.
+#[derive(Resource, Debug, Clone)]
+pub struct VisibilityRulesHandle(pub Handle<VisibilityRules>);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Error emitted by [`VisibilityRulesLoader`].
+#[derive(Debug)]
+pub enum VisibilityRulesLoaderError
+{
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl Display for VisibilityRulesLoaderError
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            Self::Io(err)  => write!(f, "failed reading visibility rules: {err}"),
+            Self::Ron(err) => write!(f, "failed parsing visibility rules: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for VisibilityRulesLoaderError {}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Loads [`VisibilityRules`] from `.vis.ron` files.
+#[derive(Default)]
+pub struct VisibilityRulesLoader;
+
+impl AssetLoader for VisibilityRulesLoader
+{
+    type Asset = VisibilityRules;
+    type Settings = ();
+    type Error = VisibilityRulesLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error>
+    {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await.map_err(VisibilityRulesLoaderError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(VisibilityRulesLoaderError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &["vis.ron"]
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extends `App` with methods for registering names that can be used in [`VisibilityRules`].
+pub trait VisibilityRulesAppExt
+{
+    /// Registers a component that can be used to select entities in visibility rules.
+    fn register_visibility_rule_component<T: Component>(&mut self, name: impl Into<String>) -> &mut Self;
+
+    /// Registers an attribute that can be referenced in visibility rule conditions.
+    fn register_visibility_rule_attribute<T: VisibilityAttribute>(&mut self, name: impl Into<String>) -> &mut Self;
+
+    /// Registers a condition parameter that can be referenced in visibility rule conditions.
+    ///
+    /// The parameter is bound from component `C` (see [`VisibilityParamsAppExt::register_condition_param`]).
+    fn register_visibility_rule_param<C: Component>(&mut self, name: impl Into<String>) -> &mut Self;
+}
+
+impl VisibilityRulesAppExt for App
+{
+    fn register_visibility_rule_component<T: Component>(&mut self, name: impl Into<String>) -> &mut Self
+    {
+        let component_id = self.world_mut().register_component::<T>();
+        if self.world_mut()
+            .get_resource_or_init::<VisibilityRuleRegistry>()
+            .components
+            .insert(name.into(), component_id)
+            .is_some()
+        { panic!("visibility rule component names must be unique"); }
+
+        self.add_observer(apply_rules_on_insert::<T>)
+            .add_observer(apply_rules_on_remove::<T>)
+    }
+
+    fn register_visibility_rule_attribute<T: VisibilityAttribute>(&mut self, name: impl Into<String>) -> &mut Self
+    {
+        if self.world_mut()
+            .get_resource_or_init::<VisibilityRuleRegistry>()
+            .attributes
+            .insert(name.into(), VisibilityAttributeId::new::<T>)
+            .is_some()
+        { panic!("visibility rule attribute names must be unique"); }
+
+        self
+    }
+
+    fn register_visibility_rule_param<C: Component>(&mut self, name: impl Into<String>) -> &mut Self
+    {
+        if self.world_mut()
+            .get_resource_or_init::<VisibilityRuleRegistry>()
+            .params
+            .insert(name.into(), VisibilityParamId::of::<C>())
+            .is_some()
+        { panic!("visibility rule param names must be unique"); }
+
+        self
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Plugin that applies [`VisibilityRules`] assets to entities.
+///
+/// The builtin [`Global`] and [`Client`] attributes are registered with the names `"Global"` and `"Client"`.
+///
+/// Requires the `rules` feature.
+pub struct VisibilityRulesPlugin;
+
+impl Plugin for VisibilityRulesPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.init_asset::<VisibilityRules>()
+            .init_asset_loader::<VisibilityRulesLoader>()
+            .init_resource::<VisibilityRuleRegistry>()
+            .init_resource::<CompiledVisibilityRules>()
+            .register_visibility_rule_attribute::<Global>("Global")
+            .register_visibility_rule_attribute::<Client>("Client")
+            .register_visibility_layer::<VisibilityRules>()
+            .add_systems(PostUpdate,
+                (
+                    compile_visibility_rules,
+                    apply_visibility_rules,
+                )
+                    .chain()
+                    .before(VisibilityLayerSet)
+            );
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
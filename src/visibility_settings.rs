@@ -0,0 +1,163 @@
+//local shortcuts
+use crate::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resource with optional settings for [`VisibilityAttributesPlugin`].
+///
+/// The plugin initializes default settings if this resource is missing, so it can be inserted before or after adding
+/// the plugin. Changes to the settings are applied at runtime, although default attributes and conditions are only
+/// applied to clients and entities that are added after the change.
+///
+/// Example:
+/**
+```rust
+app.insert_resource(
+        VisibilitySettings::default()
+            .with_default_condition(Global)
+            .with_linger(VisibilityLinger::Ticks(10))
+    )
+    .add_plugins(VisibilityAttributesPlugin{
+        server_id: None,
+        reconnect_policy: ReconnectPolicy::Reset,
+    });
+```
+*/
+#[derive(Resource, Debug, Clone)]
+pub struct VisibilitySettings
+{
+    default_condition: Option<VisibilityCondition>,
+    warn_missing_condition: bool,
+    default_attributes: Vec<DefaultClientAttribute>,
+    builtin_attributes: bool,
+    linger: Option<VisibilityLinger>,
+    budget: VisibilityBudget,
+}
+
+impl VisibilitySettings
+{
+    /// Sets a condition inserted on `Replicated` entities that are spawned without a [`VisibilityCondition`].
+    ///
+    /// For example, `with_default_condition(Global)` makes entities visible to all clients unless they are given a
+    /// condition.
+    ///
+    /// Missing conditions are detected in [`VisibilityUpdateSet`], after conditions inserted by this crate's other
+    /// features (e.g. [`VisibilityLayers`](VisibilityLayer)) are applied. Entities are only checked in the tick they
+    /// get the `Replicated` component, so removing an entity's condition will not re-apply the default.
+    pub fn with_default_condition(mut self, condition: impl IntoVisibilityCondition) -> Self
+    {
+        self.default_condition = Some(VisibilityCondition::new(condition));
+        self
+    }
+
+    /// Logs a warning for each `Replicated` entity spawned without a [`VisibilityCondition`].
+    ///
+    /// This is useful for finding entities that are invisible to all clients because they have no condition. Entities
+    /// are checked at the same time as for [`Self::with_default_condition`].
+    pub fn with_missing_condition_warning(mut self) -> Self
+    {
+        self.warn_missing_condition = true;
+        self
+    }
+
+    /// Adds an attribute given to clients when they start replicating, in addition to the builtin [`Global`] and
+    /// [`Client`] attributes.
+    ///
+    /// See [`DefaultClientAttribute`].
+    pub fn with_default_attribute(mut self, attribute: DefaultClientAttribute) -> Self
+    {
+        self.default_attributes.push(attribute);
+        self
+    }
+
+    /// Disables the builtin [`Global`] and [`Client`] attributes that are given to clients by default.
+    ///
+    /// Note that [`ServerEventSender`] events sent with `vis!(Global)` will not reach clients without the [`Global`]
+    /// attribute.
+    pub fn without_builtin_attributes(mut self) -> Self
+    {
+        self.builtin_attributes = false;
+        self
+    }
+
+    /// Sets the linger applied to all entities that don't have their own [`VisibilityLinger`] component.
+    pub fn with_linger(mut self, linger: VisibilityLinger) -> Self
+    {
+        self.linger = Some(linger);
+        self
+    }
+
+    /// Sets limits on revealing entities to clients. See [`VisibilityBudget`].
+    ///
+    /// If [`VisibilityBudget::max_visible`] is set while clients are connected, then only entities revealed after
+    /// the change count toward the limit.
+    pub fn with_budget(mut self, budget: VisibilityBudget) -> Self
+    {
+        self.budget = budget;
+        self
+    }
+
+    /// Gets the default condition. See [`Self::with_default_condition`].
+    pub fn default_condition(&self) -> Option<&VisibilityCondition>
+    {
+        self.default_condition.as_ref()
+    }
+
+    /// Returns `true` if missing conditions are logged. See [`Self::with_missing_condition_warning`].
+    pub fn warns_missing_condition(&self) -> bool
+    {
+        self.warn_missing_condition
+    }
+
+    /// Gets the default linger. See [`Self::with_linger`].
+    pub fn linger(&self) -> Option<VisibilityLinger>
+    {
+        self.linger
+    }
+
+    /// Gets the visibility budget. See [`Self::with_budget`].
+    pub fn budget(&self) -> VisibilityBudget
+    {
+        self.budget
+    }
+
+    /// Collects the default attributes of a client.
+    ///
+    /// The client entity is `None` for the server player.
+    pub(crate) fn collect_default_attributes(&self, client_id: u64, client_entity: Option<&EntityRef>)
+        -> Vec<VisibilityAttributeId>
+    {
+        let mut attributes = Vec::with_capacity(self.default_attributes.len() + 2);
+        if self.builtin_attributes
+        {
+            attributes.push(Global.attribute_id());
+            attributes.push(Client(client_id).attribute_id());
+        }
+        attributes.extend(self.default_attributes.iter().filter_map(|a| a.create(client_id, client_entity)));
+        attributes
+    }
+}
+
+impl Default for VisibilitySettings
+{
+    fn default() -> Self
+    {
+        Self{
+            default_condition: None,
+            warn_missing_condition: false,
+            default_attributes: Vec::default(),
+            builtin_attributes: true,
+            linger: None,
+            budget: VisibilityBudget::default(),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
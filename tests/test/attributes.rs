@@ -0,0 +1,234 @@
+//local shortcuts
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct Unit;
+
+#[derive(VisibilityAttribute)]
+enum Team
+{
+    Red,
+    Blue,
+    Custom(String),
+    Numbered{ id: u32 },
+}
+
+#[derive(VisibilityAttribute)]
+struct InLocation(u32, u32);
+
+#[derive(VisibilityAttribute)]
+struct Named
+{
+    zone: u16,
+    name: &'static str,
+}
+
+#[derive(VisibilityAttribute)]
+struct Generic<T: Send + Sync + 'static>(T);
+
+#[derive(VisibilityAttribute)]
+#[visibility_attribute(packed)]
+struct InCell
+{
+    x: u32,
+    y: u32,
+}
+
+#[derive(VisibilityAttribute)]
+#[visibility_attribute(packed)]
+struct Signed(i16, bool);
+
+#[derive(VisibilityAttribute)]
+#[visibility_attribute(packed)]
+enum Packed
+{
+    A,
+    B(u8),
+    C(u16, u16),
+}
+
+#[derive(Debug)]
+struct Colliding(u32, u32);
+
+impl VisibilityAttribute for Colliding
+{
+    fn inner_attribute_id(&self) -> u64 { (self.0 + self.1) as u64 }
+}
+
+#[derive(Debug)]
+struct CollidingLogged(u32, u32);
+
+impl VisibilityAttribute for CollidingLogged
+{
+    fn inner_attribute_id(&self) -> u64 { (self.0 + self.1) as u64 }
+}
+
+#[derive(Debug)]
+struct CollidingUnchecked(u32, u32);
+
+impl VisibilityAttribute for CollidingUnchecked
+{
+    fn inner_attribute_id(&self) -> u64 { (self.0 + self.1) as u64 }
+}
+
+#[derive(Debug)]
+struct CollidingOther(u32, u32);
+
+impl VisibilityAttribute for CollidingOther
+{
+    fn inner_attribute_id(&self) -> u64 { (self.0 + self.1) as u64 }
+}
+
+#[derive(Hash)]
+struct CollidingHashed(u32, u32);
+
+impl VisibilityAttribute for CollidingHashed
+{
+    fn inner_attribute_id(&self) -> u64 { (self.0 + self.1) as u64 }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn derive_unit_struct()
+{
+    assert_eq!(Unit.inner_attribute_id(), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn derive_enum_variants()
+{
+    assert_eq!(Team::Red.inner_attribute_id(), hash_visibility_attribute(&(0u64,)));
+    assert_eq!(Team::Blue.inner_attribute_id(), hash_visibility_attribute(&(1u64,)));
+    assert_ne!(Team::Red.attribute_id(), Team::Blue.attribute_id());
+
+    // payloads
+    assert_eq!(Team::Custom("a".into()).attribute_id(), Team::Custom("a".into()).attribute_id());
+    assert_ne!(Team::Custom("a".into()).attribute_id(), Team::Custom("b".into()).attribute_id());
+    assert_eq!(Team::Numbered{ id: 2 }.attribute_id(), Team::Numbered{ id: 2 }.attribute_id());
+    assert_ne!(Team::Numbered{ id: 2 }.attribute_id(), Team::Numbered{ id: 3 }.attribute_id());
+
+    // evaluation
+    let condition = vis!(or(Team::Red, Team::Custom("a".into())));
+    assert!(condition.evaluate(|a| a == Team::Red.attribute_id()));
+    assert!(condition.evaluate(|a| a == Team::Custom("a".into()).attribute_id()));
+    assert!(!condition.evaluate(|a| a == Team::Blue.attribute_id()));
+    assert!(!condition.evaluate(|a| a == Team::Custom("b".into()).attribute_id()));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn derive_hashed_struct()
+{
+    assert_eq!(InLocation(1, 2).attribute_id(), InLocation(1, 2).attribute_id());
+    assert_ne!(InLocation(1, 2).attribute_id(), InLocation(2, 1).attribute_id());
+    assert_eq!(InLocation(1, 2).inner_attribute_id(), hash_visibility_attribute(&(&1u32, &2u32)));
+
+    assert_eq!(Named{ zone: 1, name: "a" }.attribute_id(), Named{ zone: 1, name: "a" }.attribute_id());
+    assert_ne!(Named{ zone: 1, name: "a" }.attribute_id(), Named{ zone: 1, name: "b" }.attribute_id());
+
+    assert_eq!(Generic(5u8).attribute_id(), Generic(5u8).attribute_id());
+    assert_ne!(Generic(5u8).attribute_id(), Generic(6u8).attribute_id());
+    assert_ne!(Generic(5u8).attribute_id(), Generic(5u16).attribute_id());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn hashed_ids_are_stable()
+{
+    // These values must not change between releases.
+    assert_eq!(hash_visibility_attribute(&(1u32, "red")), 2623222328568302080);
+    assert_eq!(hash_visibility_attribute(&(7usize, -3i16, true)), 3921532375320552762);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn derive_packed_struct()
+{
+    assert_eq!(InCell{ x: 0, y: 0 }.inner_attribute_id(), 0);
+    assert_eq!(InCell{ x: 1, y: 2 }.inner_attribute_id(), (1u64 << 32) + 2);
+    assert_eq!(InCell{ x: u32::MAX, y: 0 }.inner_attribute_id(), (u32::MAX as u64) << 32);
+
+    // no sign extension
+    assert_eq!(Signed(-1, false).inner_attribute_id(), 0xFFFF << 1);
+    assert_eq!(Signed(-1, true).inner_attribute_id(), (0xFFFF << 1) + 1);
+    assert_eq!(Signed(0, true).inner_attribute_id(), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn derive_packed_enum()
+{
+    // 3 variants = 2 tag bits
+    assert_eq!(Packed::A.inner_attribute_id(), 0);
+    assert_eq!(Packed::B(0).inner_attribute_id(), 1u64 << 62);
+    assert_eq!(Packed::B(7).inner_attribute_id(), (1u64 << 62) + 7);
+    assert_eq!(Packed::C(1, 2).inner_attribute_id(), (2u64 << 62) + (1 << 16) + 2);
+    assert_ne!(Packed::B(1).attribute_id(), Packed::C(0, 1).attribute_id());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+#[should_panic]
+fn collision_detected()
+{
+    detect_attribute_collisions::<Colliding>(AttributeCollisionResponse::Panic);
+    let _ = vis!(Colliding(1, 2));
+    let _ = vis!(and(Colliding(1, 2), Colliding(0, 0)));
+    let _ = vis!(Colliding(2, 1));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn collision_logged()
+{
+    detect_attribute_collisions::<CollidingLogged>(AttributeCollisionResponse::Log);
+    let _ = vis!(CollidingLogged(1, 2));
+    let _ = vis!(CollidingLogged(1, 2));
+    assert_eq!(attribute_collision_count::<CollidingLogged>(), 0);
+    let _ = vis!(CollidingLogged(2, 1));
+    assert_eq!(attribute_collision_count::<CollidingLogged>(), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn collision_logged_by_hash()
+{
+    detect_attribute_collisions_by_hash::<CollidingHashed>(AttributeCollisionResponse::Log);
+    let _ = vis!(and(CollidingHashed(1, 2), CollidingHashed(1, 2)));
+    assert_eq!(attribute_collision_count::<CollidingHashed>(), 0);
+    let _ = vis!(CollidingHashed(2, 1));
+    assert_eq!(attribute_collision_count::<CollidingHashed>(), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn collision_not_registered()
+{
+    detect_attribute_collisions::<CollidingOther>(AttributeCollisionResponse::Panic);
+    let _ = vis!(CollidingOther(1, 2));
+    let _ = vis!(CollidingUnchecked(1, 2));
+    let _ = vis!(CollidingUnchecked(2, 1));
+    assert_eq!(attribute_collision_count::<CollidingUnchecked>(), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
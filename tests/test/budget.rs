@@ -0,0 +1,139 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct InCity;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Component, Default, Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct Important;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup(visibility_budget: VisibilityBudget) -> (App, App)
+{
+    let (mut server_app, mut client_app) = common::setup(VisibilitySettings::default().with_budget(visibility_budget));
+    for app in [&mut server_app, &mut client_app] {
+        app.replicate::<Important>();
+    }
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn num_replicated<C: Component>(client_app: &mut App) -> usize
+{
+    client_app
+        .world_mut()
+        .query_filtered::<Entity, (With<Replicated>, With<C>)>()
+        .iter(client_app.world())
+        .count()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn force_visible(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.force_visible(client_id, entity);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn reveals_admitted_in_priority_order()
+{
+    let (mut server_app, mut client_app) = setup(VisibilityBudget{ max_reveals_per_tick: Some(2), max_visible: None });
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    for _ in 0..3
+    {
+        server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCity)));
+    }
+    for _ in 0..2
+    {
+        server_app.world_mut().spawn((Replicated, Important, vis!(InCity), VisibilityPriority(10)));
+    }
+    update(&mut server_app, &mut client_app);
+
+    server_app.world_mut().syscall((client_id, InCity), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated::<Important>(&mut client_app), 2);
+    assert_eq!(num_replicated::<ComponentA>(&mut client_app), 0);
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated::<ComponentA>(&mut client_app), 2);
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated::<ComponentA>(&mut client_app), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn pending_reveals_cancelled_when_hidden()
+{
+    let (mut server_app, mut client_app) = setup(VisibilityBudget{ max_reveals_per_tick: Some(1), max_visible: None });
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    for _ in 0..3
+    {
+        server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCity)));
+    }
+
+    server_app.world_mut().syscall((client_id, InCity), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated::<ComponentA>(&mut client_app), 1);
+
+    server_app.world_mut().syscall((client_id, InCity), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated::<ComponentA>(&mut client_app), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn max_visible_limits_reveals()
+{
+    let (mut server_app, mut client_app) = setup(VisibilityBudget{ max_reveals_per_tick: None, max_visible: Some(2) });
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let forced = server_app.world_mut().spawn((Replicated, Important)).id();
+    let a = server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCity))).id();
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCity)));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCity)));
+
+    // forced visibility bypasses the budget but counts toward the max
+    server_app.world_mut().syscall((client_id, forced), force_visible);
+    server_app.world_mut().syscall((client_id, InCity), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated::<Important>(&mut client_app), 1);
+    assert_eq!(num_replicated::<ComponentA>(&mut client_app), 1);
+
+    // room is made when visible entities are despawned
+    server_app.world_mut().despawn(forced);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated::<ComponentA>(&mut client_app), 2);
+
+    server_app.world_mut().entity_mut(a).insert(vis!());
+    update(&mut server_app, &mut client_app);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated::<ComponentA>(&mut client_app), 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
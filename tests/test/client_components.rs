@@ -0,0 +1,125 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::{prelude::*, shared::backend::connected_client::NetworkId, test_app::{ServerTestAppExt, TestClientEntity}};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Component, VisibilityAttribute)]
+struct InZone(u32);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup() -> (App, App)
+{
+    let (mut server_app, client_app) = common::setup(VisibilitySettings::default());
+    server_app.register_client_attribute_component::<InZone>();
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn has_attribute(In((client_id, attribute)): In<(u64, VisibilityAttributeId)>, attributes: ClientAttributes) -> bool
+{
+    attributes.get(client_id).map(|a| a.contains(&attribute)).unwrap_or_default()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn num_replicated(client_app: &mut App) -> usize
+{
+    client_app
+        .world_mut()
+        .query_filtered::<Entity, (With<Replicated>, With<ComponentA>)>()
+        .iter(client_app.world())
+        .count()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+// inserting, replacing, and removing an attribute component updates the client's attributes
+#[test]
+fn component_attribute_lifecycle()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let client_entity = **client_app.world().resource::<TestClientEntity>();
+
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InZone(1))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InZone(2))));
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 0);
+
+    // insert
+    server_app.world_mut().entity_mut(client_entity).insert(InZone(1));
+    assert!(server_app.world_mut().syscall((client_id, InZone(1).attribute_id()), has_attribute));
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 1);
+
+    // replace
+    server_app.world_mut().entity_mut(client_entity).insert(InZone(2));
+    assert!(!server_app.world_mut().syscall((client_id, InZone(1).attribute_id()), has_attribute));
+    assert!(server_app.world_mut().syscall((client_id, InZone(2).attribute_id()), has_attribute));
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 1);
+
+    // remove
+    server_app.world_mut().entity_mut(client_entity).remove::<InZone>();
+    assert!(!server_app.world_mut().syscall((client_id, InZone(2).attribute_id()), has_attribute));
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// attribute components inserted before the client has a network id are added when the id is inserted
+#[test]
+fn component_attribute_before_network_id()
+{
+    let (mut server_app, mut client_app) = setup();
+    server_app.connect_client(&mut client_app);
+    let client_entity = **client_app.world().resource::<TestClientEntity>();
+    server_app.world_mut().entity_mut(client_entity).insert(InZone(3));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InZone(3))));
+    assert!(!server_app.world_mut().syscall((1u64, InZone(3).attribute_id()), has_attribute));
+
+    server_app.world_mut().entity_mut(client_entity).insert(NetworkId::new(1));
+    assert!(server_app.world_mut().syscall((1u64, InZone(3).attribute_id()), has_attribute));
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// unregistered attribute components are ignored
+#[test]
+fn component_attribute_unregistered()
+{
+    #[derive(Component, VisibilityAttribute, Default, PartialEq)]
+    struct Unregistered;
+
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let client_entity = **client_app.world().resource::<TestClientEntity>();
+
+    server_app.world_mut().entity_mut(client_entity).insert(Unregistered);
+    assert!(!server_app.world_mut().syscall((client_id, Unregistered.attribute_id()), has_attribute));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -2,7 +2,7 @@
 use bevy_replicon_attributes::prelude::*;
 
 //third-party shortcuts
-use bevy::prelude::*;
+use bevy::{app::Plugins, prelude::*};
 use bevy_replicon::{prelude::*, shared::backend::connected_client::NetworkId, test_app::{ServerTestAppExt, TestClientEntity}};
 use serde::{Deserialize, Serialize};
 
@@ -17,6 +17,56 @@ pub(super) struct DummyComponent;
 #[derive(Component, Default, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub(super) struct BasicComponent(pub(super) usize);
 
+#[derive(Component, Default, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(super) struct ComponentA;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Sets up a server and client that replicate [`ComponentA`], with visibility attributes on the server.
+///
+/// Extra registrations can be added to the returned apps.
+pub(super) fn setup(settings: VisibilitySettings) -> (App, App)
+{
+    setup_with(settings, ())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Same as [`setup`], with extra plugins added to the server before the apps are finished.
+pub(super) fn setup_with<M>(settings: VisibilitySettings, plugins: impl Plugins<M>) -> (App, App)
+{
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            RepliconPlugins.set(bevy_replicon::prelude::ServerPlugin {
+                tick_policy: TickPolicy::EveryFrame,
+                visibility_policy: VisibilityPolicy::Whitelist,
+                ..Default::default()
+            }),
+        ))
+        .replicate::<ComponentA>();
+    }
+    server_app
+        .insert_resource(settings)
+        .add_plugins(VisibilityAttributesPlugin{ server_id: None, reconnect_policy: ReconnectPolicy::Reset })
+        .add_plugins(plugins);
+    client_app.finish();
+    server_app.finish();
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(super) fn update(server_app: &mut App, client_app: &mut App)
+{
+    server_app.update();
+    server_app.exchange_with_client(client_app);
+    client_app.update();
+}
+
 //-------------------------------------------------------------------------------------------------------------------
 
 pub(super) fn connect(server_app: &mut App, client_app: &mut App, client_id: u64) -> u64
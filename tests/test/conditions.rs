@@ -451,3 +451,121 @@ fn removal_by_type()
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn replacement_where()
+{
+    let mut a = vis!(and(Manual(1), or(not(Manual(2)), A)));
+    let count = a.replace_where(|attr| attr.is::<Manual>().then(|| Manual(attr.inner_id() as usize + 100)));
+    assert_eq!(count, 2);
+    assert_eq!(a, vis!(and(Manual(101), or(not(Manual(102)), A))));
+
+    // replace with a branch
+    let count = a.replace_where(|attr| (attr == A.attribute_id()).then(|| and(B, C)));
+    assert_eq!(count, 1);
+    assert_eq!(a, vis!(and(Manual(101), or(not(Manual(102)), and(B, C)))));
+
+    // replace with empty
+    let count = a.replace_where(|attr| attr.is::<Manual>().then(empty));
+    assert_eq!(count, 2);
+    assert_eq!(a, vis!(and(B, C)));
+
+    // no replacements
+    let count = a.replace_where(|_| None::<A>);
+    assert_eq!(count, 0);
+    assert_eq!(a, vis!(and(B, C)));
+
+    let mut e = vis!();
+    assert_eq!(e.replace_where(|_| Some(A)), 0);
+    assert_eq!(e, vis!());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn contains_pattern()
+{
+    let a = vis!(and(A, or(not(B), Manual(1))));
+    assert!(a.contains(A));
+    assert!(a.contains(B));
+    assert!(a.contains(not(B)));
+    assert!(a.contains(Manual(1)));
+    assert!(a.contains(or(not(B), Manual(1))));
+    assert!(a.contains(a.clone()));
+    assert!(!a.contains(Manual(2)));
+    assert!(!a.contains(C));
+    assert!(!a.contains(and(A, not(B))));
+    assert!(!a.contains(or(B, Manual(1))));
+    assert!(!a.contains(empty()));
+    assert!(!vis!().contains(A));
+    assert!(!vis!(A).contains(and(A, B)));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn attributes_of_type()
+{
+    let a = vis!(or(Manual(3), and(Manual2(4), not(Manual(5)))));
+    assert_eq!(a.attributes_of_type::<Manual>().collect::<Vec<_>>(), vec![3, 5]);
+    assert_eq!(a.attributes_of_type::<Manual2>().collect::<Vec<_>>(), vec![4]);
+    assert_eq!(a.attributes_of_type::<A>().count(), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn depth_and_node_count()
+{
+    assert_eq!(vis!().depth(), 0);
+    assert_eq!(vis!().node_count(), 0);
+    assert_eq!(vis!(A).depth(), 1);
+    assert_eq!(vis!(A).node_count(), 1);
+    assert_eq!(vis!(not(A)).depth(), 2);
+    assert_eq!(vis!(not(A)).node_count(), 2);
+    assert_eq!(vis!(and(A, B)).depth(), 2);
+    assert_eq!(vis!(and(A, B)).node_count(), 3);
+    assert_eq!(vis!(and(A, or(B, not(C)))).depth(), 4);
+    assert_eq!(vis!(and(A, or(B, not(C)))).node_count(), 6);
+    assert_eq!(vis!(and(or(not(not(A)), B), C)).depth(), 5);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn large_condition_interning()
+{
+    let shares_allocation = |a: &VisibilityCondition, b: &VisibilityCondition| -> bool
+    {
+        match (a, b)
+        {
+            (VisibilityCondition::Large(a), VisibilityCondition::Large(b)) => std::sync::Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    };
+
+    // identical large conditions share an allocation
+    let a = vis!(and(Manual(1000), not(Manual2(1000))));
+    let b = vis!(and(Manual(1000), not(Manual2(1000))));
+    assert_eq!(a, b);
+    assert!(shares_allocation(&a, &b));
+
+    // conditions built in different ways share an allocation
+    let mut c = vis!(Manual(1000));
+    c.and(not(Manual2(1000)));
+    assert!(shares_allocation(&a, &c));
+
+    // different conditions don't share an allocation
+    let d = vis!(and(Manual(1000), not(Manual2(1001))));
+    assert!(!shares_allocation(&a, &d));
+
+    // dropped conditions are re-allocated
+    drop(a);
+    drop(b);
+    drop(c);
+    let e = vis!(and(Manual(1000), not(Manual2(1000))));
+    let f = vis!(and(Manual(1000), not(Manual2(1000))));
+    assert!(shares_allocation(&e, &f));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,113 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::{prelude::*, shared::backend::connected_client::NetworkId, test_app::{ServerTestAppExt, TestClientEntity}};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct InLobby;
+
+#[derive(VisibilityAttribute)]
+struct ProtocolVersion(u32);
+
+#[derive(Component)]
+struct ClientProtocol(u32);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Connects a client whose entity has a protocol component.
+fn connect_with_protocol(server_app: &mut App, client_app: &mut App, client_id: u64, protocol: u32)
+{
+    server_app.connect_client(client_app);
+    let entity = **client_app.world().resource::<TestClientEntity>();
+    server_app.world_mut().entity_mut(entity).insert((NetworkId::new(client_id), ClientProtocol(protocol)));
+    server_app.world_mut().flush();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn default_attributes_added_on_connect()
+{
+    let (mut server_app, mut client_app) = setup(
+        VisibilitySettings::default()
+            .with_default_attribute(DefaultClientAttribute::new(InLobby))
+            .with_default_attribute(DefaultClientAttribute::from_component(|p: &ClientProtocol| ProtocolVersion(p.0)))
+    );
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(and(InLobby, ProtocolVersion(2)))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(ProtocolVersion(1))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(Global)));
+
+    connect_with_protocol(&mut server_app, &mut client_app, 1, 2);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn default_attributes_inserted_after_plugin()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    server_app.insert_resource(VisibilitySettings::default().with_default_attribute(DefaultClientAttribute::new(InLobby)));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InLobby)));
+
+    common::connect(&mut server_app, &mut client_app, 1);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn default_attributes_missing_component()
+{
+    let (mut server_app, mut client_app) = setup(
+        VisibilitySettings::default()
+            .with_default_attribute(DefaultClientAttribute::from_component(|p: &ClientProtocol| ProtocolVersion(p.0)))
+            .with_default_attribute(DefaultClientAttribute::from_client_id(|id| ProtocolVersion(id as u32 + 10)))
+    );
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(ProtocolVersion(2))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(ProtocolVersion(11))));
+
+    common::connect(&mut server_app, &mut client_app, 1);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn builtin_attributes_disabled()
+{
+    let (mut server_app, mut client_app) = setup(
+        VisibilitySettings::default()
+            .with_default_attribute(DefaultClientAttribute::new(InLobby))
+            .without_builtin_attributes()
+    );
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(Global)));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(Client(1))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InLobby)));
+
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // builtins can still be added manually
+    server_app.world_mut().syscall((client_id, Global), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
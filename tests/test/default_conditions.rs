@@ -0,0 +1,107 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct A;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Component)]
+struct Owner;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn default_condition_applied_to_missing()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default().with_default_condition(Global));
+    common::connect(&mut server_app, &mut client_app, 1);
+
+    let missing = server_app.world_mut().spawn((Replicated, ComponentA)).id();
+    let explicit = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(missing), Some(&vis!(Global)));
+    assert_eq!(server_app.world().get::<VisibilityCondition>(explicit), Some(&vis!(A)));
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // the default is only applied on spawn
+    server_app.world_mut().entity_mut(missing).remove::<VisibilityCondition>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(missing), None);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn settings_inserted_after_plugin()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    server_app.insert_resource(VisibilitySettings::default().with_default_condition(Global));
+    common::connect(&mut server_app, &mut client_app, 1);
+
+    let missing = server_app.world_mut().spawn((Replicated, ComponentA)).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(missing), Some(&vis!(Global)));
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn default_condition_does_not_override_inherited()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default().with_default_condition(Global));
+    common::connect(&mut server_app, &mut client_app, 1);
+
+    let parent = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    let child = server_app.world_mut().spawn((Replicated, ComponentA, InheritVisibility::new(), ChildOf(parent))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(child), None);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn default_condition_does_not_override_layers()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default().with_default_condition(Global));
+    server_app.derive_visibility_condition::<Owner>(|_| vis!(A));
+    common::connect(&mut server_app, &mut client_app, 1);
+
+    // the derived condition is inserted in the same tick the entity is spawned
+    let owned = server_app.world_mut().spawn((Replicated, ComponentA, Owner)).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(owned), Some(&vis!(A)));
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn warn_missing_condition_does_not_insert()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default().with_missing_condition_warning());
+    common::connect(&mut server_app, &mut client_app, 1);
+
+    let missing = server_app.world_mut().spawn((Replicated, ComponentA)).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(missing), None);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
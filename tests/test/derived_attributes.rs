@@ -0,0 +1,174 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute)]
+struct InTeam(u32);
+
+#[derive(Component)]
+struct Team(u32);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup() -> (App, App)
+{
+    let (mut server_app, client_app) = common::setup(VisibilitySettings::default());
+    server_app.derive_client_attributes(|team: &Team| InTeam(team.0));
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn has_attribute(In((client_id, attribute)): In<(u64, VisibilityAttributeId)>, attributes: ClientAttributes) -> bool
+{
+    attributes.get(client_id).map(|a| a.contains(&attribute)).unwrap_or_default()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn in_team(app: &mut App, client_id: u64, team: u32) -> bool
+{
+    app.world_mut().syscall((client_id, InTeam(team).attribute_id()), has_attribute)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn num_replicated(client_app: &mut App) -> usize
+{
+    client_app
+        .world_mut()
+        .query_filtered::<Entity, (With<Replicated>, With<ComponentA>)>()
+        .iter(client_app.world())
+        .count()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+// derived attributes follow changes to the controlled entity's component
+#[test]
+fn derived_attribute_follows_component()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InTeam(1))));
+
+    let player = server_app.world_mut().spawn((Team(1), ControlledBy(client_id))).id();
+    update(&mut server_app, &mut client_app);
+    assert!(in_team(&mut server_app, client_id, 1));
+    assert_eq!(num_replicated(&mut client_app), 1);
+
+    // mutate in place
+    server_app.world_mut().get_mut::<Team>(player).unwrap().0 = 2;
+    update(&mut server_app, &mut client_app);
+    assert!(!in_team(&mut server_app, client_id, 1));
+    assert!(in_team(&mut server_app, client_id, 2));
+    assert_eq!(num_replicated(&mut client_app), 0);
+
+    // replace
+    server_app.world_mut().entity_mut(player).insert(Team(1));
+    update(&mut server_app, &mut client_app);
+    assert!(!in_team(&mut server_app, client_id, 2));
+    assert!(in_team(&mut server_app, client_id, 1));
+    assert_eq!(num_replicated(&mut client_app), 1);
+
+    // remove
+    server_app.world_mut().entity_mut(player).remove::<Team>();
+    assert!(!in_team(&mut server_app, client_id, 1));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// derived attributes move with ownership transfers
+#[test]
+fn derived_attribute_ownership_transfer()
+{
+    let (mut server_app, _client_app) = setup();
+
+    let player = server_app.world_mut().spawn((Team(1), ControlledBy(1))).id();
+    server_app.update();
+    assert!(in_team(&mut server_app, 1, 1));
+    assert!(!in_team(&mut server_app, 2, 1));
+
+    server_app.world_mut().entity_mut(player).insert(ControlledBy(2));
+    server_app.update();
+    assert!(!in_team(&mut server_app, 1, 1));
+    assert!(in_team(&mut server_app, 2, 1));
+
+    server_app.world_mut().entity_mut(player).remove::<ControlledBy>();
+    assert!(!in_team(&mut server_app, 2, 1));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// re-inserting equal components keeps the derived attribute
+#[test]
+fn derived_attribute_equal_reinsert()
+{
+    let (mut server_app, _client_app) = setup();
+
+    let player = server_app.world_mut().spawn((Team(1), ControlledBy(1))).id();
+    server_app.update();
+    assert!(in_team(&mut server_app, 1, 1));
+
+    server_app.world_mut().entity_mut(player).insert((Team(1), ControlledBy(1)));
+    assert!(in_team(&mut server_app, 1, 1));
+    server_app.update();
+    assert!(in_team(&mut server_app, 1, 1));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// attributes derived by multiple controlled entities are only removed when all are gone
+#[test]
+fn derived_attribute_shared_by_entities()
+{
+    let (mut server_app, _client_app) = setup();
+
+    let a = server_app.world_mut().spawn((Team(1), ControlledBy(1))).id();
+    let b = server_app.world_mut().spawn((Team(1), ControlledBy(1))).id();
+    server_app.update();
+    assert!(in_team(&mut server_app, 1, 1));
+
+    server_app.world_mut().despawn(a);
+    server_app.update();
+    assert!(in_team(&mut server_app, 1, 1));
+
+    server_app.world_mut().despawn(b);
+    server_app.update();
+    assert!(!in_team(&mut server_app, 1, 1));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// derived attributes are added to clients that connect after the controlled entity was spawned
+#[test]
+fn derived_attribute_before_connect()
+{
+    let (mut server_app, mut client_app) = setup();
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InTeam(1))));
+    server_app.world_mut().spawn((Team(1), ControlledBy(1)));
+    server_app.update();
+
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    update(&mut server_app, &mut client_app);
+    assert!(in_team(&mut server_app, client_id, 1));
+    assert_eq!(num_replicated(&mut client_app), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
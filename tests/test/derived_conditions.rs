@@ -0,0 +1,116 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct IsAdmin;
+
+#[derive(Component)]
+struct Owner(u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup() -> (App, App)
+{
+    let (mut server_app, client_app) = common::setup(VisibilitySettings::default());
+    server_app.derive_visibility_condition::<Owner>(|owner| vis!(or(Client(owner.0), IsAdmin)));
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn num_replicated(client_app: &mut App) -> usize
+{
+    client_app
+        .world_mut()
+        .query_filtered::<Entity, (With<Replicated>, With<ComponentA>)>()
+        .iter(client_app.world())
+        .count()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+// derived conditions follow the source component
+#[test]
+fn derived_condition_follows_component()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+
+    let item = server_app.world_mut().spawn((Replicated, ComponentA, Owner(client_id))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(
+        server_app.world().get::<VisibilityCondition>(item),
+        Some(&vis!(or(Client(client_id), IsAdmin)))
+    );
+    assert_eq!(num_replicated(&mut client_app), 1);
+
+    // mutate in place
+    server_app.world_mut().get_mut::<Owner>(item).unwrap().0 = 2;
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(item), Some(&vis!(or(Client(2), IsAdmin))));
+    assert_eq!(num_replicated(&mut client_app), 0);
+
+    // replace
+    server_app.world_mut().entity_mut(item).insert(Owner(client_id));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 1);
+
+    // remove
+    server_app.world_mut().entity_mut(item).remove::<Owner>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(item), None);
+    assert_eq!(num_replicated(&mut client_app), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// derived conditions are evaluated against client attributes
+#[test]
+fn derived_condition_attribute_changes()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+
+    server_app.world_mut().spawn((Replicated, ComponentA, Owner(2)));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 0);
+
+    server_app.world_mut().syscall((client_id, IsAdmin), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// despawning an entity with a derived condition is handled
+#[test]
+fn derived_condition_despawn()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+
+    let item = server_app.world_mut().spawn((Replicated, ComponentA, Owner(client_id))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 1);
+
+    server_app.world_mut().despawn(item);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(num_replicated(&mut client_app), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,123 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup(stale_after: u32) -> (App, App)
+{
+    common::setup_with(VisibilitySettings::default(), FogOfWarPlugin{ stale_after })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn observe(server_app: &mut App, team: u64, entity: Entity)
+{
+    server_app.world_mut().get_mut::<SeenByTeams>(entity).unwrap().observe(team);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn join_team(In((client_id, team)): In<(u64, u64)>, mut attributes: ClientAttributes)
+{
+    attributes.add(client_id, InTeam(team));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn team_observations_reveal_entities()
+{
+    let (mut server_app, mut client_app) = setup(1);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, 1), join_team);
+    let a = server_app.world_mut().spawn((Replicated, ComponentA, FogOfWar)).id();
+    let b = server_app.world_mut().spawn((Replicated, ComponentA, FogOfWar)).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // Observations from other teams are not shared.
+    observe(&mut server_app, 1, a);
+    observe(&mut server_app, 2, b);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    assert!(server_app.world().get::<SeenByTeams>(b).unwrap().is_observed_by(2));
+
+    // Clients see the union of their teams' observations.
+    server_app.world_mut().syscall((client_id, 2), join_team);
+    observe(&mut server_app, 1, a);
+    observe(&mut server_app, 2, a);
+    observe(&mut server_app, 2, b);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(a), Some(&vis!(or(InTeam(1), InTeam(2)))));
+
+    // Removing fog of war removes the layer and observations.
+    server_app.world_mut().entity_mut(a).remove::<FogOfWar>();
+    observe(&mut server_app, 2, b);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    assert!(server_app.world().get::<VisibilityLayer<FogOfWar>>(a).is_none());
+    assert!(server_app.world().get::<SeenByTeams>(a).is_none());
+    assert!(server_app.world().get::<VisibilityCondition>(a).is_none());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn observations_go_stale()
+{
+    let (mut server_app, mut client_app) = setup(3);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, 1), join_team);
+    let entity = server_app.world_mut().spawn((Replicated, ComponentA, FogOfWar)).id();
+    observe(&mut server_app, 1, entity);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Re-observing refreshes the observation.
+    update(&mut server_app, &mut client_app);
+    observe(&mut server_app, 1, entity);
+    update(&mut server_app, &mut client_app);
+    update(&mut server_app, &mut client_app);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+    assert_eq!(server_app.world().get::<SeenByTeams>(entity).unwrap().teams().count(), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn expired_observations_are_reported()
+{
+    let (mut server_app, mut client_app) = setup(1);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, 1), join_team);
+    let entity = server_app.world_mut().spawn((Replicated, ComponentA, FogOfWar)).id();
+    observe(&mut server_app, 1, entity);
+    observe(&mut server_app, 2, entity);
+    update(&mut server_app, &mut client_app);
+    assert!(server_app.world().resource::<Events<TeamObservationExpired>>().is_empty());
+
+    // Only the team that stops reporting is expired.
+    observe(&mut server_app, 2, entity);
+    update(&mut server_app, &mut client_app);
+    let expired: Vec<_> = server_app.world_mut().resource_mut::<Events<TeamObservationExpired>>().drain().collect();
+    assert_eq!(expired, vec![TeamObservationExpired{ team: 1, entity }]);
+    assert_eq!(client_app.world().entities().len(), 3);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(entity), Some(&vis!(InTeam(2))));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,166 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::{prelude::*, test_app::ServerTestAppExt};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct HasNightVision;
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct CanFly;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn grant<T: VisibilityAttribute>(In((client_id, attribute, source)): In<(u64, T, AttributeSource)>, mut attributes: ClientAttributes)
+{
+    attributes.grant(client_id, attribute, source);
+}
+
+fn revoke<T: VisibilityAttribute>(In((client_id, attribute, source)): In<(u64, T, AttributeSource)>, mut attributes: ClientAttributes)
+{
+    attributes.revoke(client_id, attribute, source);
+}
+
+fn revoke_all_from(In(source): In<AttributeSource>, mut attributes: ClientAttributes)
+{
+    attributes.revoke_all_from(source);
+}
+
+fn num_sources<T: VisibilityAttribute>(In((client_id, attribute)): In<(u64, T)>, attributes: ClientAttributes) -> usize
+{
+    attributes.sources(client_id, attribute).count()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn grant_stays_until_all_sources_revoked()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(HasNightVision)));
+
+    let buff = server_app.world_mut().spawn_empty().id();
+    server_app.world_mut().syscall((client_id, HasNightVision, buff.into()), grant);
+    server_app.world_mut().syscall((client_id, HasNightVision, "item".into()), grant);
+    // repeated grants from one source count once
+    server_app.world_mut().syscall((client_id, HasNightVision, "item".into()), grant);
+    assert_eq!(server_app.world_mut().syscall((client_id, HasNightVision), num_sources), 2);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // revoking one source keeps the attribute
+    server_app.world_mut().syscall((client_id, HasNightVision, buff.into()), revoke);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // revoking an unknown source does nothing
+    server_app.world_mut().syscall((client_id, HasNightVision, 7u64.into()), revoke);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // revoking the last source removes the attribute
+    server_app.world_mut().syscall((client_id, HasNightVision, "item".into()), revoke);
+    assert_eq!(server_app.world_mut().syscall((client_id, HasNightVision), num_sources), 0);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn direct_adds_count_as_a_source()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(HasNightVision)));
+
+    // add + grant + remove keeps the granted attribute
+    server_app.world_mut().syscall((client_id, HasNightVision), add_attribute);
+    server_app.world_mut().syscall((client_id, HasNightVision, "item".into()), grant);
+    server_app.world_mut().syscall((client_id, HasNightVision), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // revoking the last grant removes the attribute
+    server_app.world_mut().syscall((client_id, HasNightVision, "item".into()), revoke);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // grant + add + revoke keeps the directly added attribute
+    server_app.world_mut().syscall((client_id, HasNightVision, "item".into()), grant);
+    server_app.world_mut().syscall((client_id, HasNightVision), add_attribute);
+    server_app.world_mut().syscall((client_id, HasNightVision, "item".into()), revoke);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // removing the direct add removes the attribute
+    server_app.world_mut().syscall((client_id, HasNightVision), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn revoke_all_from_source()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(HasNightVision)));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(CanFly)));
+
+    server_app.world_mut().syscall((client_id, HasNightVision, "potion".into()), grant);
+    server_app.world_mut().syscall((client_id, CanFly, "potion".into()), grant);
+    server_app.world_mut().syscall((client_id, CanFly, "wings".into()), grant);
+    server_app.world_mut().syscall((2, CanFly, "potion".into()), grant);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().syscall("potion".into(), revoke_all_from);
+    assert_eq!(server_app.world_mut().syscall((2, CanFly), num_sources), 0);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    server_app.world_mut().syscall("wings".into(), revoke_all_from);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn grants_kept_across_reset()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(HasNightVision)));
+
+    // Grant before the client connects.
+    server_app.world_mut().syscall((1u64, HasNightVision, "item".into()), grant);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    assert_eq!(server_app.world_mut().syscall((client_id, HasNightVision), num_sources), 1);
+
+    // Disconnecting doesn't clear grants.
+    server_app.disconnect_client(&mut client_app);
+    assert_eq!(server_app.world_mut().syscall((client_id, HasNightVision), num_sources), 1);
+
+    // Revoking still works while disconnected.
+    server_app.world_mut().syscall((client_id, HasNightVision, "item".into()), revoke);
+    assert_eq!(server_app.world_mut().syscall((client_id, HasNightVision), num_sources), 0);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
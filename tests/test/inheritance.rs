@@ -0,0 +1,137 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct A;
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct B;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn condition(app: &App, entity: Entity) -> Option<&VisibilityCondition>
+{
+    app.world().get::<VisibilityCondition>(entity)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn inherit_through_chain()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let parent = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    let child = server_app.world_mut().spawn((Replicated, ComponentA, InheritVisibility::new(), ChildOf(parent))).id();
+    let grandchild = server_app.world_mut().spawn((Replicated, ComponentA, InheritVisibility::and(B), ChildOf(child))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(condition(&server_app, child), None);
+    assert_eq!(condition(&server_app, grandchild), Some(&vis!(B)));
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // parent visibility propagates down the chain
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().syscall((client_id, B), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    // parent changes propagate down the chain
+    server_app.world_mut().entity_mut(parent).insert(vis!(Global));
+    server_app.world_mut().syscall((client_id, A), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    // parent condition removed
+    server_app.world_mut().entity_mut(parent).remove::<VisibilityCondition>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn inherit_hierarchy_changes()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    let parent_a = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    let parent_b = server_app.world_mut().spawn((Replicated, ComponentA, vis!(B))).id();
+    let child = server_app.world_mut().spawn((Replicated, ComponentA, InheritVisibility::new(), ChildOf(parent_a))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // reparent
+    server_app.world_mut().entity_mut(child).insert(ChildOf(parent_b));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // orphaned entities are hidden
+    server_app.world_mut().syscall((client_id, B), add_attribute);
+    server_app.world_mut().entity_mut(child).remove::<ChildOf>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // change own condition
+    server_app.world_mut().syscall((client_id, B), remove_attribute);
+    server_app.world_mut().entity_mut(child).insert((ChildOf(parent_a), InheritVisibility::and(B)));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(condition(&server_app, child), Some(&vis!(B)));
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    server_app.world_mut().syscall((client_id, B), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    // stop inheriting
+    server_app.world_mut().entity_mut(child).remove::<InheritVisibility>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(condition(&server_app, child), None);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn inherit_replication()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+
+    let parent = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    server_app.world_mut().spawn((Replicated, ComponentA, InheritVisibility::new(), ChildOf(parent)));
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // parent and child become visible together
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // parent and child become hidden together
+    server_app.world_mut().entity_mut(parent).insert(vis!(B));
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
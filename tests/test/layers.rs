@@ -0,0 +1,181 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+use bevy_replicon_attributes::not;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct A;
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct B;
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct C;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+struct FogOfWar;
+struct Ownership;
+
+#[derive(Component)]
+struct Owner(bool);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup_app() -> App
+{
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .register_visibility_layer::<FogOfWar>()
+        .register_visibility_layer::<Ownership>();
+    app
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn condition(app: &App, entity: Entity) -> Option<&VisibilityCondition>
+{
+    app.world().get::<VisibilityCondition>(entity)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn layers_combined_with_and()
+{
+    let mut app = setup_app();
+    let entity = app.world_mut().spawn((
+        VisibilityLayer::<FogOfWar>::new(A),
+        VisibilityLayer::<Ownership>::new(or(B, C)),
+    )).id();
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(and(A, or(B, C)))));
+
+    // change one layer
+    app.world_mut().get_mut::<VisibilityLayer<FogOfWar>>(entity).unwrap().set(not(A));
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(and(not(A), or(B, C)))));
+    assert_eq!(app.world().get::<VisibilityLayer<Ownership>>(entity).unwrap().condition(), &vis!(or(B, C)));
+
+    // empty layers hide the entity
+    app.world_mut().entity_mut(entity).insert(VisibilityLayer::<Ownership>::new(vis!()));
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!()));
+
+    // remove a layer
+    app.world_mut().entity_mut(entity).remove::<VisibilityLayer<Ownership>>();
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(not(A))));
+
+    // remove the last layer
+    app.world_mut().entity_mut(entity).remove::<VisibilityLayer<FogOfWar>>();
+    app.update();
+    assert_eq!(condition(&app, entity), None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn layer_policy_change()
+{
+    let mut app = setup_app();
+    let entity = app.world_mut().spawn((
+        VisibilityLayer::<FogOfWar>::new(A),
+        VisibilityLayer::<Ownership>::new(B),
+    )).id();
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(and(A, B))));
+
+    *app.world_mut().resource_mut::<VisibilityLayerPolicy>() = VisibilityLayerPolicy::Or;
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(or(A, B))));
+
+    *app.world_mut().resource_mut::<VisibilityLayerPolicy>() =
+        VisibilityLayerPolicy::Custom(|layers| layers.last().map(|l| (*l).clone()).unwrap_or_else(VisibilityCondition::empty));
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(B)));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn unregistered_layer_ignored()
+{
+    struct Unregistered;
+
+    let mut app = setup_app();
+    let entity = app.world_mut().spawn((
+        VisibilityLayer::<FogOfWar>::new(A),
+        VisibilityLayer::<Unregistered>::new(B),
+    )).id();
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(A)));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn derived_condition_is_layer()
+{
+    let mut app = setup_app();
+    app.derive_visibility_condition::<Owner>(|owner| match owner.0 { true => vis!(B), false => vis!(C) });
+    let entity = app.world_mut().spawn((VisibilityLayer::<FogOfWar>::new(A), Owner(true))).id();
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(and(A, B))));
+
+    // the derived layer doesn't overwrite other layers
+    app.world_mut().get_mut::<Owner>(entity).unwrap().0 = false;
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(and(A, C))));
+
+    // removing the component only removes its layer
+    app.world_mut().entity_mut(entity).remove::<Owner>();
+    app.update();
+    assert_eq!(condition(&app, entity), Some(&vis!(A)));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn layers_replicate()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    server_app
+        .register_visibility_layer::<FogOfWar>()
+        .register_visibility_layer::<Ownership>();
+
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+
+    let entity = server_app.world_mut().spawn((
+        Replicated,
+        ComponentA,
+        VisibilityLayer::<FogOfWar>::new(A),
+        VisibilityLayer::<Ownership>::new(B),
+    )).id();
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // relax one layer
+    server_app.world_mut().get_mut::<VisibilityLayer<Ownership>>(entity).unwrap().set(Global);
+
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,122 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct InZone;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup(settings: VisibilitySettings) -> (App, App)
+{
+    let (mut server_app, client_app) = common::setup(settings);
+    server_app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn force_hidden(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.force_hidden(client_id, entity);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn entity_linger_ticks()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InZone), VisibilityLinger::Ticks(2)));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InZone)));
+    server_app.world_mut().syscall((client_id, InZone), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // leaving the zone only hides the entity without a linger
+    server_app.world_mut().syscall((client_id, InZone), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // re-entering before the linger expires keeps the entity
+    server_app.world_mut().syscall((client_id, InZone), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+    update(&mut server_app, &mut client_app);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // linger expires
+    server_app.world_mut().syscall((client_id, InZone), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn default_linger_duration()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default().with_linger(VisibilityLinger::Duration(Duration::from_millis(150))));
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InZone)));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InZone), VisibilityLinger::Ticks(0)));
+    server_app.world_mut().syscall((client_id, InZone), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // the entity's own linger takes precedence
+    server_app.world_mut().syscall((client_id, InZone), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn linger_ignored_for_overrides_and_despawns()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default().with_linger(VisibilityLinger::Ticks(5)));
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let a = server_app.world_mut().spawn((Replicated, ComponentA, vis!(InZone))).id();
+    let b = server_app.world_mut().spawn((Replicated, ComponentA, vis!(InZone))).id();
+    server_app.world_mut().syscall((client_id, InZone), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // forced hidden
+    server_app.world_mut().syscall((client_id, a), force_hidden);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // despawned
+    server_app.world_mut().despawn(b);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
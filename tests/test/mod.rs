@@ -1,7 +1,33 @@
 //module tree
+mod attributes;
+mod budget;
+mod client_components;
+#[allow(dead_code)]
 mod common;
 mod conditions;
+mod default_attributes;
+mod default_conditions;
+mod derived_attributes;
+mod derived_conditions;
+#[allow(clippy::identity_op, clippy::needless_borrow, clippy::default_constructed_unit_structs)]
 mod replication;
+mod room_graph;
+mod scheduled_attributes;
+mod spatial_grid;
+mod sync;
+mod visible_with;
+#[allow(clippy::single_element_loop)]
 mod events;
+mod fog_of_war;
+mod grants;
+mod inheritance;
+mod layers;
+mod linger;
+mod overrides;
+mod params;
+mod presets;
+mod predicates;
+#[cfg(feature = "rules")]
+mod rules;
 
 pub(crate) use common::*;
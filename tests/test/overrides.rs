@@ -0,0 +1,166 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::{prelude::*, shared::backend::connected_client::NetworkId, test_app::TestClientEntity};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct A;
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct B;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn force_visible(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.force_visible(client_id, entity);
+}
+
+fn force_hidden(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.force_hidden(client_id, entity);
+}
+
+fn clear_override(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.clear_override(client_id, entity);
+}
+
+fn clear_overrides(In(client_id): In<u64>, mut attributes: ClientAttributes)
+{
+    attributes.clear_overrides(client_id);
+}
+
+fn get_override(In((client_id, entity)): In<(u64, Entity)>, attributes: ClientAttributes) -> Option<bool>
+{
+    attributes.get_override(client_id, entity)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn force_visible_overrides_condition()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+
+    let entity = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // forced visible
+    server_app.world_mut().syscall((client_id, entity), force_visible);
+    assert_eq!(server_app.world_mut().syscall((client_id, entity), get_override), Some(true));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // survives condition changes
+    server_app.world_mut().entity_mut(entity).insert(vis!(B));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // survives condition removal
+    server_app.world_mut().entity_mut(entity).remove::<VisibilityCondition>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // clear
+    server_app.world_mut().entity_mut(entity).insert(vis!(B));
+    server_app.world_mut().syscall((client_id, entity), clear_override);
+    assert_eq!(server_app.world_mut().syscall((client_id, entity), get_override), None);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn force_hidden_overrides_condition()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+
+    let entity = server_app.world_mut().spawn((Replicated, ComponentA, vis!(Global))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // forced hidden
+    server_app.world_mut().syscall((client_id, entity), force_hidden);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // survives attribute and condition changes
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    server_app.world_mut().entity_mut(entity).insert(vis!(A));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // clear
+    server_app.world_mut().syscall((client_id, entity), clear_override);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn overrides_cleaned_up()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+
+    let a = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    let b = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    server_app.world_mut().syscall((client_id, a), force_visible);
+    server_app.world_mut().syscall((client_id, b), force_hidden);
+    update(&mut server_app, &mut client_app);
+
+    // entity despawn
+    server_app.world_mut().despawn(a);
+    assert_eq!(server_app.world_mut().syscall((client_id, a), get_override), None);
+    assert_eq!(server_app.world_mut().syscall((client_id, b), get_override), Some(false));
+
+    // client disconnect
+    let client_entity = **client_app.world().resource::<TestClientEntity>();
+    server_app.world_mut().entity_mut(client_entity).remove::<NetworkId>();
+    assert_eq!(server_app.world_mut().syscall((client_id, b), get_override), None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn clear_all_overrides()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+
+    let a = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    let b = server_app.world_mut().spawn((Replicated, ComponentA, vis!(Global))).id();
+    let c = server_app.world_mut().spawn((Replicated, ComponentA, vis!(Global))).id();
+    server_app.world_mut().syscall((client_id, a), force_visible);
+    server_app.world_mut().syscall((client_id, b), force_hidden);
+    server_app.world_mut().syscall((client_id, c), force_hidden);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // visibility falls back to conditions
+    server_app.world_mut().syscall(client_id, clear_overrides);
+    assert_eq!(server_app.world_mut().syscall((client_id, a), get_override), None);
+    assert_eq!(server_app.world_mut().syscall((client_id, b), get_override), None);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,208 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+use bevy_replicon_attributes::not;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct IsSpectator;
+
+#[derive(VisibilityAttribute, Debug, Copy, Clone, Eq, PartialEq)]
+struct InZone(u64);
+
+#[derive(VisibilityAttribute, Debug, Copy, Clone, Eq, PartialEq)]
+struct InTeam(u64);
+
+#[derive(Component)]
+struct Zone(u64);
+
+#[derive(Component)]
+struct Team(u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup() -> (App, App)
+{
+    let (mut server_app, client_app) = common::setup(VisibilitySettings::default());
+    server_app
+        .register_condition_param::<Zone>(|zone| vis!(InZone(zone.0)))
+        .register_condition_param::<Team>(|team| vis!(InTeam(team.0)));
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn params_are_bound_per_entity()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, InZone(1)), add_attribute);
+    server_app.world_mut().syscall((client_id, InTeam(1)), add_attribute);
+
+    let template = vis!(and(param::<Zone>(), param::<Team>()));
+    server_app.world_mut().spawn((Replicated, ComponentA, Zone(1), Team(1), template.clone()));
+    let b = server_app.world_mut().spawn((Replicated, ComponentA, Zone(2), Team(1), template.clone())).id();
+    let c = server_app.world_mut().spawn((Replicated, ComponentA, Zone(1), Team(2), template.clone())).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Changing a bound component only re-evaluates that entity, and the template is unchanged.
+    let last_changed = server_app.world().entity(c).get_ref::<VisibilityCondition>().unwrap().last_changed();
+    server_app.world_mut().entity_mut(b).insert(Zone(1));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(b), Some(&template));
+    assert_eq!(server_app.world().entity(c).get_ref::<VisibilityCondition>().unwrap().last_changed(), last_changed);
+
+    server_app.world_mut().entity_mut(c).get_mut::<Team>().unwrap().0 = 1;
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    // Unbound parameters are not satisfied.
+    server_app.world_mut().entity_mut(c).remove::<Team>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // Client attribute changes apply to bound conditions.
+    server_app.world_mut().syscall((client_id, InZone(1)), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn params_combine_with_attributes()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, InZone(1)), add_attribute);
+    server_app.world_mut().spawn((Replicated, ComponentA, Zone(1), vis!(and(param::<Zone>(), not(IsSpectator)))));
+    server_app.world_mut().spawn((Replicated, ComponentA, Zone(2), vis!(not(param::<Zone>()))));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().syscall((client_id, IsSpectator), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Replacing the extractor rebinds all entities.
+    server_app.register_condition_param::<Zone>(|zone| vis!(InZone(zone.0 - 1)));
+    server_app.world_mut().syscall((client_id, IsSpectator), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    server_app.world_mut().syscall((client_id, InZone(0)), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn unbound_params_are_unknown()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, IsSpectator), add_attribute);
+    let a = server_app.world_mut().spawn((Replicated, ComponentA, vis!(not(param::<Zone>())))).id();
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(or(IsSpectator, param::<Zone>()))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(and(IsSpectator, param::<Zone>()))));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Binding the parameter lets the condition be evaluated.
+    server_app.world_mut().entity_mut(a).insert(Zone(1));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().syscall((client_id, InZone(1)), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn params_work_with_linked_entities()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, InZone(1)), add_attribute);
+    server_app.world_mut().syscall((client_id, InTeam(1)), add_attribute);
+
+    // Linked entities follow the anchor's bindings, and extra conditions bind against their own components.
+    let anchor = server_app.world_mut().spawn((Replicated, ComponentA, Zone(1), vis!(param::<Zone>()))).id();
+    server_app.world_mut().spawn((Replicated, ComponentA, VisibleWith(anchor)));
+    server_app.world_mut().spawn((Replicated, ComponentA, Team(1), InheritVisibility::and(param::<Team>()), ChildOf(anchor)));
+    server_app.world_mut().spawn((Replicated, ComponentA, Team(2), InheritVisibility::and(param::<Team>()), ChildOf(anchor)));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    // Rebinding the anchor updates linked entities.
+    server_app.world_mut().entity_mut(anchor).insert(Zone(2));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    server_app.world_mut().syscall((client_id, InZone(2)), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn replace_params()
+{
+    let mut condition = vis!(or(param::<Zone>(), and(param::<Team>(), not(param::<Zone>()))));
+    assert_eq!(condition.iter_params().count(), 3);
+    assert!(!condition.evaluate(|_| true));
+
+    let replaced = condition.replace_params(
+        |param|
+        {
+            if param != VisibilityParamId::of::<Zone>() { return None; }
+            Some(InZone(3))
+        }
+    );
+    assert_eq!(replaced, 2);
+    assert_eq!(condition, vis!(or(InZone(3), and(param::<Team>(), not(InZone(3))))));
+    assert!(condition.has_params());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn evaluate_with_params()
+{
+    let condition = vis!(not(param::<Zone>()));
+    let bound = vis!(InZone(1));
+    let nested = vis!(param::<Team>());
+    assert!(!condition.evaluate_with_params(|_| false, |_| false, |_| None));
+    assert!(!condition.evaluate_with_params(|_| false, |_| false, |_| Some(&nested)));
+    assert!(condition.evaluate_with_params(|_| false, |_| false, |_| Some(&bound)));
+    assert!(!condition.evaluate_with_params(|_| true, |_| false, |_| Some(&bound)));
+
+    // Unbound parameters are unknown.
+    let either = vis!(or(InTeam(1), param::<Zone>()));
+    assert!(either.evaluate_with_params(|_| true, |_| false, |_| None));
+    assert!(!either.evaluate_with_params(|_| false, |_| false, |_| None));
+    let both = vis!(not(and(InTeam(1), param::<Zone>())));
+    assert!(both.evaluate_with_params(|_| false, |_| false, |_| None));
+    assert!(!both.evaluate_with_params(|_| true, |_| false, |_| None));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,252 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+use bevy_replicon_attributes::not;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct A;
+
+struct Allowed;
+
+#[derive(Resource, Default)]
+struct AllowedPairs(HashSet<(u64, Entity)>);
+
+#[derive(Resource, Default)]
+struct PredicateRuns(AtomicUsize);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn is_allowed(In(pairs): In<Vec<(u64, Entity)>>, allowed: Res<AllowedPairs>, runs: Res<PredicateRuns>) -> Vec<bool>
+{
+    runs.0.fetch_add(1, Ordering::Relaxed);
+    pairs.into_iter().map(|pair| allowed.0.contains(&pair)).collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn allow_all(In(pairs): In<Vec<(u64, Entity)>>) -> Vec<bool>
+{
+    vec![true; pairs.len()]
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn force_hidden(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.force_hidden(client_id, entity);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn clear_override(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.clear_override(client_id, entity);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup(cadence: PredicateCadence) -> (App, App)
+{
+    let (mut server_app, client_app) = common::setup(VisibilitySettings::default());
+    server_app
+        .init_resource::<AllowedPairs>()
+        .init_resource::<PredicateRuns>()
+        .register_visibility_predicate::<Allowed, _>(cadence, is_allowed);
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn runs(server_app: &mut App) -> usize
+{
+    server_app.world().resource::<PredicateRuns>().0.load(Ordering::Relaxed)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn allow(server_app: &mut App, client_id: u64, entity: Entity, allowed: bool)
+{
+    let mut pairs = server_app.world_mut().resource_mut::<AllowedPairs>();
+    match allowed
+    {
+        true  => { pairs.0.insert((client_id, entity)); }
+        false => { pairs.0.remove(&(client_id, entity)); }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn predicates_combine_with_attributes()
+{
+    let (mut server_app, mut client_app) = setup(PredicateCadence::Ticks(1));
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let a = server_app.world_mut().spawn((Replicated, ComponentA, vis!(and(A, predicate::<Allowed>())))).id();
+    let b = server_app.world_mut().spawn((Replicated, ComponentA, vis!(or(A, predicate::<Allowed>())))).id();
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(not(predicate::<Allowed>()))));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Predicate results are applied on the next evaluation.
+    allow(&mut server_app, client_id, a, true);
+    allow(&mut server_app, client_id, b, true);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // Attribute changes use the last predicate results.
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    allow(&mut server_app, client_id, a, false);
+    allow(&mut server_app, client_id, b, false);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().syscall((client_id, A), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn predicates_follow_cadence()
+{
+    let (mut server_app, mut client_app) = setup(PredicateCadence::Ticks(3));
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    update(&mut server_app, &mut client_app);
+
+    // New entities are evaluated immediately.
+    let entity = server_app.world_mut().spawn_empty().id();
+    allow(&mut server_app, client_id, entity, true);
+    server_app.world_mut().entity_mut(entity).insert((Replicated, ComponentA, vis!(predicate::<Allowed>())));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Existing entities are re-evaluated at the cadence.
+    allow(&mut server_app, client_id, entity, false);
+    let mut updates = 0;
+    while client_app.world().entities().len() == 3 + 1
+    {
+        update(&mut server_app, &mut client_app);
+        updates += 1;
+    }
+    assert!(updates <= 3);
+
+    allow(&mut server_app, client_id, entity, true);
+    update(&mut server_app, &mut client_app);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn predicate_conditions_can_change()
+{
+    let (mut server_app, mut client_app) = setup(PredicateCadence::Ticks(1));
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let entity = server_app.world_mut().spawn((Replicated, ComponentA, vis!(predicate::<Allowed>()))).id();
+    allow(&mut server_app, client_id, entity, true);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Switching to an attribute condition hides the entity.
+    server_app.world_mut().entity_mut(entity).insert(vis!(A));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // Switching back re-evaluates the predicate.
+    server_app.world_mut().entity_mut(entity).insert(vis!(predicate::<Allowed>()));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Overrides take precedence.
+    server_app.world_mut().syscall((client_id, entity), force_hidden);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    server_app.world_mut().syscall((client_id, entity), clear_override);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn predicates_are_batched()
+{
+    let (mut server_app, mut client_app) = setup(PredicateCadence::Ticks(1));
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    for _ in 0..3
+    {
+        let entity = server_app.world_mut().spawn((Replicated, ComponentA, vis!(predicate::<Allowed>()))).id();
+        allow(&mut server_app, client_id, entity, true);
+    }
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(or(A, predicate::<Allowed>()))));
+
+    // The predicate runs once per evaluation for all clients and entities.
+    let prev_runs = runs(&mut server_app);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(runs(&mut server_app), prev_runs + 1);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn unused_predicate_results_are_dropped()
+{
+    let (mut server_app, mut client_app) = setup(PredicateCadence::Ticks(100));
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let entity = server_app.world_mut().spawn((Replicated, ComponentA, vis!(predicate::<Allowed>()))).id();
+    allow(&mut server_app, client_id, entity, true);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Results are dropped when the entity's condition stops using the predicate, so they are not reused when the
+    // predicate is used again before its next scheduled evaluation.
+    server_app.world_mut().entity_mut(entity).insert(vis!(A));
+    update(&mut server_app, &mut client_app);
+    allow(&mut server_app, client_id, entity, false);
+    server_app.world_mut().entity_mut(entity).insert(vis!(predicate::<Allowed>()));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn registering_predicates_again_replaces_them()
+{
+    let (mut server_app, mut client_app) = setup(PredicateCadence::Ticks(100));
+    common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(predicate::<Allowed>())));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // The replaced predicate is re-evaluated immediately.
+    server_app.register_visibility_predicate::<Allowed, _>(PredicateCadence::Ticks(100), allow_all);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
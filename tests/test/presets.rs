@@ -0,0 +1,144 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+use bevy_replicon_attributes::not;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct IsSpectator;
+
+#[derive(VisibilityAttribute, Debug, Copy, Clone, Eq, PartialEq)]
+struct InRoom(u64);
+
+#[derive(Component)]
+struct Room(u64);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup() -> (App, App)
+{
+    let (mut server_app, client_app) = common::setup(VisibilitySettings::default());
+    server_app.register_condition_param::<Room>(|room| vis!(InRoom(room.0)));
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn presets(server_app: &mut App) -> Mut<'_, VisibilityPresets>
+{
+    server_app.world_mut().resource_mut::<VisibilityPresets>()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn redefining_presets_updates_users()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, InRoom(3)), add_attribute);
+    let loot = presets(&mut server_app).define("BossRoomLoot", InRoom(3));
+    let other = presets(&mut server_app).define("Other", InRoom(4));
+    for _ in 0..3
+    {
+        server_app.world_mut().spawn((Replicated, ComponentA, VisibilityPreset(loot)));
+    }
+    let entity = server_app.world_mut().spawn((Replicated, ComponentA, VisibilityPreset(other))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+    assert_eq!(presets(&mut server_app).users(loot).count(), 3);
+
+    // Redefining a preset keeps its handle.
+    server_app.world_mut().syscall((client_id, IsSpectator), add_attribute);
+    assert_eq!(presets(&mut server_app).define("BossRoomLoot", and(InRoom(3), not(IsSpectator))), loot);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    presets(&mut server_app).define("Other", InRoom(3));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Switching presets.
+    server_app.world_mut().entity_mut(entity).insert(VisibilityPreset(loot));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+    assert_eq!(presets(&mut server_app).users(loot).count(), 4);
+    assert_eq!(presets(&mut server_app).users(other).count(), 0);
+
+    server_app.world_mut().syscall((client_id, IsSpectator), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 4);
+
+    // Removing the preset removes the condition.
+    server_app.world_mut().entity_mut(entity).remove::<VisibilityPreset>();
+    update(&mut server_app, &mut client_app);
+    assert!(server_app.world().get::<VisibilityCondition>(entity).is_none());
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn presets_with_params()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, InRoom(1)), add_attribute);
+    let preset = presets(&mut server_app).define("RoomLoot", param::<Room>());
+    server_app.world_mut().spawn((Replicated, ComponentA, Room(1), VisibilityPreset(preset)));
+    server_app.world_mut().spawn((Replicated, ComponentA, Room(2), VisibilityPreset(preset)));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    presets(&mut server_app).define("RoomLoot", or(param::<Room>(), IsSpectator));
+    server_app.world_mut().syscall((client_id, IsSpectator), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn unknown_presets_are_applied_when_defined()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, InRoom(3)), add_attribute);
+    let known = presets(&mut server_app).define("Known", InRoom(3));
+
+    // Handles from another presets resource are unknown until the server defines them.
+    let mut other_presets = VisibilityPresets::default();
+    other_presets.define("Known", InRoom(3));
+    let unknown = other_presets.define("Unknown", InRoom(3));
+    let pending = server_app.world_mut().spawn((Replicated, ComponentA, VisibilityPreset(unknown))).id();
+    let replaced = server_app.world_mut().spawn((Replicated, ComponentA, VisibilityPreset(known))).id();
+    update(&mut server_app, &mut client_app);
+    assert!(server_app.world().get::<VisibilityCondition>(pending).is_none());
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Replacing a preset with an unknown preset clears the old condition.
+    server_app.world_mut().entity_mut(replaced).insert(VisibilityPreset(unknown));
+    update(&mut server_app, &mut client_app);
+    assert!(server_app.world().get::<VisibilityCondition>(replaced).is_none());
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // Defining the preset applies it to waiting entities.
+    assert_eq!(presets(&mut server_app).define("Unknown", InRoom(3)), unknown);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(pending), Some(&vis!(InRoom(3))));
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,176 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup(max_hops: u32) -> (App, App)
+{
+    common::setup_with(VisibilitySettings::default(), RoomGraphPlugin{ max_hops })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn spawn_in(server_app: &mut App, room: u64) -> Entity
+{
+    server_app.world_mut().spawn((Replicated, ComponentA, CurrentRoom(room))).id()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn graph(server_app: &mut App) -> Mut<'_, RoomGraph>
+{
+    server_app.world_mut().resource_mut::<RoomGraph>()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn avatar_sees_own_room()
+{
+    let (mut server_app, mut client_app) = setup(1);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let avatar = server_app.world_mut().spawn((RoomAvatar(client_id), CurrentRoom(1))).id();
+    spawn_in(&mut server_app, 1);
+    spawn_in(&mut server_app, 2);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Moving the avatar moves the client.
+    server_app.world_mut().entity_mut(avatar).insert(CurrentRoom(2));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Replicated avatars are visible to the clients that can see their room.
+    server_app.world_mut().entity_mut(avatar).insert((Replicated, ComponentA));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().entity_mut(avatar).remove::<RoomAvatar>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn toggling_edges_updates_visibility()
+{
+    let (mut server_app, mut client_app) = setup(1);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    graph(&mut server_app).connect(1, 2, false);
+    graph(&mut server_app).connect(2, 3, true);
+    server_app.world_mut().spawn((RoomAvatar(client_id), CurrentRoom(1)));
+    spawn_in(&mut server_app, 1);
+    let room2 = spawn_in(&mut server_app, 2);
+    let room3 = spawn_in(&mut server_app, 3);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Opening the door reveals the next room, but not rooms further away.
+    let last_changed = server_app.world().entity(room3).get_ref::<VisibilityCondition>().unwrap().last_changed();
+    graph(&mut server_app).set_open(1, 2, true);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+    assert_eq!(server_app.world().get::<VisibilityCondition>(room2), Some(&vis!(or(or(InRoom(1), InRoom(2)), InRoom(3)))));
+
+    // Rooms that are not affected by the door keep their conditions.
+    assert_eq!(server_app.world().entity(room3).get_ref::<VisibilityCondition>().unwrap().last_changed(), last_changed);
+
+    graph(&mut server_app).set_open(1, 2, false);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    graph(&mut server_app).connect(1, 3, true);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    graph(&mut server_app).disconnect(1, 3);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn multi_hop_reachability()
+{
+    let (mut server_app, mut client_app) = setup(2);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    graph(&mut server_app).connect(1, 2, true);
+    graph(&mut server_app).connect(2, 3, true);
+    graph(&mut server_app).connect(3, 4, true);
+    let avatar = server_app.world_mut().spawn((RoomAvatar(client_id), CurrentRoom(1))).id();
+    spawn_in(&mut server_app, 1);
+    spawn_in(&mut server_app, 2);
+    spawn_in(&mut server_app, 3);
+    spawn_in(&mut server_app, 4);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    graph(&mut server_app).set_open(2, 3, false);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().entity_mut(avatar).insert(CurrentRoom(3));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+    assert_eq!(graph(&mut server_app).reachable(3), [3, 4].into());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn unreplicated_entities_leave_rooms()
+{
+    let (mut server_app, mut client_app) = setup(1);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    graph(&mut server_app).connect(1, 2, false);
+    server_app.world_mut().spawn((RoomAvatar(client_id), CurrentRoom(1)));
+    let entity = spawn_in(&mut server_app, 2);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // Entities that stop replicating lose their room condition, and are not updated when their room changes.
+    server_app.world_mut().entity_mut(entity).remove::<Replicated>();
+    update(&mut server_app, &mut client_app);
+    assert!(server_app.world().get::<VisibilityCondition>(entity).is_none());
+
+    graph(&mut server_app).set_open(1, 2, true);
+    update(&mut server_app, &mut client_app);
+    assert!(server_app.world().get::<VisibilityLayer<CurrentRoom>>(entity).is_none());
+    assert!(server_app.world().get::<VisibilityCondition>(entity).is_none());
+
+    // Replicating again restores the condition.
+    server_app.world_mut().entity_mut(entity).insert(Replicated);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn room_attribute_ids_are_room_ids()
+{
+    let mut condition = vis!(or(InRoom(1), InRoom(2)));
+    assert!(condition.attributes_of_type::<InRoom>().eq([1, 2]));
+
+    condition.replace_where(
+        |attr|
+        {
+            if !attr.is::<InRoom>() { return None; }
+            Some(InRoom(attr.inner_id() + 100))
+        }
+    );
+    assert_eq!(condition, vis!(or(InRoom(101), InRoom(102))));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,219 @@
+//local shortcuts
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::{App, AssetPlugin, Assets, Component, Handle, MinimalPlugins};
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct IsDead;
+
+struct InZone(u64);
+
+impl VisibilityAttribute for InZone
+{
+    fn inner_attribute_id(&self) -> u64
+    {
+        self.0
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Component)]
+struct Loot;
+
+#[derive(Component)]
+struct Npc;
+
+#[derive(Component)]
+struct Zone;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup_app() -> App
+{
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_plugins(VisibilityRulesPlugin)
+        .register_visibility_rule_component::<Loot>("Loot")
+        .register_visibility_rule_component::<Npc>("Npc")
+        .register_visibility_rule_attribute::<IsDead>("IsDead")
+        .register_visibility_rule_attribute::<InZone>("InZone")
+        .register_visibility_rule_param::<Zone>("Zone");
+    app.finish();
+    app
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn load_rules(app: &mut App, rules: &str) -> Handle<VisibilityRules>
+{
+    let rules: VisibilityRules = ron::de::from_str(rules).unwrap();
+    let handle = app.world_mut().resource_mut::<Assets<VisibilityRules>>().add(rules);
+    app.insert_resource(VisibilityRulesHandle(handle.clone()));
+    handle
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rules_parse()
+{
+    let rules: VisibilityRules = ron::de::from_str(r#"
+        (
+            rules: [
+                (component: "Loot", condition: And(AttrId("InZone", 3), Not(Attr("IsDead")))),
+                (component: "Npc", condition: Any([Attr("Global"), Empty])),
+            ]
+        )
+    "#).unwrap();
+
+    assert_eq!(rules.rules.len(), 2);
+    assert_eq!(rules.rules[0].component, "Loot");
+    assert_eq!(rules.rules[1].condition, VisibilityRuleCondition::Any(vec![
+        VisibilityRuleCondition::Attr(String::from("Global")),
+        VisibilityRuleCondition::Empty,
+    ]));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rules_apply_to_existing_and_new_entities()
+{
+    let mut app = setup_app();
+    let existing = app.world_mut().spawn(Loot).id();
+    let unmatched = app.world_mut().spawn(vis!(IsDead)).id();
+
+    load_rules(&mut app, r#"(rules: [(component: "Loot", condition: And(AttrId("InZone", 3), Not(Attr("IsDead"))))])"#);
+    app.update();
+
+    let expected = vis!(and(InZone(3), not(IsDead)));
+    assert_eq!(app.world().get::<VisibilityCondition>(existing), Some(&expected));
+    assert_eq!(app.world().get::<VisibilityCondition>(unmatched), Some(&vis!(IsDead)));
+
+    let new = app.world_mut().spawn(Loot).id();
+    app.update();
+    assert_eq!(app.world().get::<VisibilityCondition>(new), Some(&expected));
+
+    // removing the matched component removes the rule's condition
+    app.world_mut().entity_mut(new).remove::<Loot>();
+    app.update();
+    assert_eq!(app.world().get::<VisibilityCondition>(new), None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rules_precedence()
+{
+    let mut app = setup_app();
+    let both = app.world_mut().spawn((Loot, Npc)).id();
+    let npc = app.world_mut().spawn(Npc).id();
+
+    load_rules(&mut app, r#"
+        (rules: [
+            (component: "Loot", condition: Attr("IsDead")),
+            (component: "Npc", condition: All([Attr("Global"), AttrId("InZone", 1), AttrId("InZone", 2)])),
+        ])
+    "#);
+    app.update();
+
+    assert_eq!(app.world().get::<VisibilityCondition>(both), Some(&vis!(IsDead)));
+    assert_eq!(
+        app.world().get::<VisibilityCondition>(npc),
+        Some(&vis!(all!(Global, InZone(1), InZone(2))))
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rules_reapplied_on_reload()
+{
+    let mut app = setup_app();
+    let loot = app.world_mut().spawn(Loot).id();
+    let npc = app.world_mut().spawn(Npc).id();
+
+    let handle = load_rules(&mut app, r#"(rules: [(component: "Loot", condition: Attr("IsDead"))])"#);
+    app.update();
+    assert_eq!(app.world().get::<VisibilityCondition>(loot), Some(&vis!(IsDead)));
+    assert_eq!(app.world().get::<VisibilityCondition>(npc), None);
+
+    // modify the asset in-place (equivalent to a hot-reload)
+    let new_rules: VisibilityRules = ron::de::from_str(r#"(rules: [(component: "Npc", condition: AttrId("InZone", 7))])"#)
+        .unwrap();
+    *app.world_mut().resource_mut::<Assets<VisibilityRules>>().get_mut(&handle).unwrap() = new_rules;
+    app.update();
+
+    assert_eq!(app.world().get::<VisibilityCondition>(loot), None);
+    assert_eq!(app.world().get::<VisibilityCondition>(npc), Some(&vis!(InZone(7))));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rules_invalid_rule_ignored()
+{
+    let mut app = setup_app();
+    let loot = app.world_mut().spawn(Loot).id();
+    let npc = app.world_mut().spawn(Npc).id();
+
+    load_rules(&mut app, r#"
+        (rules: [
+            (component: "Loot", condition: Attr("Unknown")),
+            (component: "Unknown", condition: Attr("Global")),
+            (component: "Npc", condition: Attr("Global")),
+        ])
+    "#);
+    app.update();
+
+    assert_eq!(app.world().get::<VisibilityCondition>(loot), None);
+    assert_eq!(app.world().get::<VisibilityCondition>(npc), Some(&vis!(Global)));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rules_with_params()
+{
+    let mut app = setup_app();
+    let loot = app.world_mut().spawn(Loot).id();
+
+    load_rules(&mut app, r#"
+        (rules: [
+            (component: "Loot", condition: And(Param("Zone"), Not(Attr("IsDead")))),
+            (component: "Npc", condition: Param("Unknown")),
+        ])
+    "#);
+    app.update();
+
+    assert_eq!(app.world().get::<VisibilityCondition>(loot), Some(&vis!(and(param::<Zone>(), not(IsDead)))));
+    let npc = app.world_mut().spawn(Npc).id();
+    app.update();
+    assert_eq!(app.world().get::<VisibilityCondition>(npc), None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn rule_components_without_plugin()
+{
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .register_visibility_rule_component::<Loot>("Loot");
+    let loot = app.world_mut().spawn(Loot).id();
+    app.update();
+    assert_eq!(app.world().get::<VisibilityCondition>(loot), None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,216 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::{prelude::*, server::server_tick::ServerTick, shared::replicon_tick::RepliconTick};
+use bevy_replicon::test_app::ServerTestAppExt;
+
+//standard shortcuts
+use std::time::Duration;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct Revealed;
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct Invited;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup() -> (App, App)
+{
+    let (mut server_app, client_app) = common::setup(VisibilitySettings::default());
+    server_app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+
+    (server_app, client_app)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn current_tick(server_app: &App) -> RepliconTick
+{
+    **server_app.world().resource::<ServerTick>()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn add_for(In((client_id, duration)): In<(u64, Duration)>, mut attributes: ClientAttributes)
+{
+    attributes.add_for(client_id, Revealed, duration);
+}
+
+fn add_at_tick(In((client_id, tick)): In<(u64, RepliconTick)>, mut attributes: ClientAttributes)
+{
+    attributes.add_at_tick(client_id, Revealed, tick);
+}
+
+fn remove_at_tick(In((client_id, tick)): In<(u64, RepliconTick)>, mut attributes: ClientAttributes)
+{
+    attributes.remove_at_tick(client_id, Revealed, tick);
+}
+
+fn get_attributes(In(client_id): In<u64>, attributes: ClientAttributes) -> Option<Vec<VisibilityAttributeId>>
+{
+    attributes.get(client_id).map(|a| a.iter().copied().collect())
+}
+
+fn has_revealed(In(client_id): In<u64>, attributes: ClientAttributes) -> bool
+{
+    attributes.get(client_id).is_some_and(|a| a.contains(&Revealed.attribute_id()))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn add_for_expires()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(Revealed)));
+    update(&mut server_app, &mut client_app);
+
+    server_app.world_mut().syscall((client_id, Duration::from_millis(150)), add_for);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // expired
+    update(&mut server_app, &mut client_app);
+    assert!(!server_app.world_mut().syscall(client_id, has_revealed));
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn add_for_refresh_extends()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.update();
+
+    server_app.world_mut().syscall((client_id, Duration::from_millis(150)), add_for);
+    server_app.update();
+    server_app.world_mut().syscall((client_id, Duration::from_millis(200)), add_for);
+    server_app.update();
+    server_app.update();
+    assert!(server_app.world_mut().syscall(client_id, has_revealed));
+    server_app.update();
+    assert!(!server_app.world_mut().syscall(client_id, has_revealed));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn add_and_remove_at_tick()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(Revealed)));
+    update(&mut server_app, &mut client_app);
+
+    let start = current_tick(&server_app);
+    server_app.world_mut().syscall((client_id, start + 2), add_at_tick);
+    server_app.world_mut().syscall((client_id, start + 3), remove_at_tick);
+
+    // tick + 1
+    update(&mut server_app, &mut client_app);
+    assert!(!server_app.world_mut().syscall(client_id, has_revealed));
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // tick + 2
+    update(&mut server_app, &mut client_app);
+    assert_eq!(current_tick(&server_app), start + 2);
+    assert!(server_app.world_mut().syscall(client_id, has_revealed));
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // tick + 3
+    update(&mut server_app, &mut client_app);
+    assert!(!server_app.world_mut().syscall(client_id, has_revealed));
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scheduled_changes_kept_across_reset()
+{
+    let (mut server_app, mut client_app) = setup();
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(Revealed)));
+    server_app.update();
+
+    // Schedule changes before the client connects, after it is given attributes.
+    server_app.world_mut().syscall((1u64, Invited), add_attribute);
+    let remove_at = current_tick(&server_app) + 10;
+    server_app.world_mut().syscall((1u64, Duration::from_secs(10)), add_for);
+    server_app.world_mut().syscall((1u64, remove_at), remove_at_tick);
+
+    // The timed attribute is re-added when the client is reset.
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    update(&mut server_app, &mut client_app);
+    assert!(server_app.world_mut().syscall(client_id, has_revealed));
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // The scheduled removal is still pending.
+    for _ in 0..10
+    {
+        if current_tick(&server_app) >= remove_at { break; }
+        update(&mut server_app, &mut client_app);
+    }
+    update(&mut server_app, &mut client_app);
+    assert!(!server_app.world_mut().syscall(client_id, has_revealed));
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scheduled_changes_ignored_for_unknown_clients()
+{
+    let (mut server_app, mut client_app) = setup();
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(Revealed)));
+    server_app.update();
+
+    // The client has no attributes yet.
+    let add_at = current_tick(&server_app) + 1;
+    server_app.world_mut().syscall((1u64, Duration::from_secs(10)), add_for);
+    server_app.world_mut().syscall((1u64, add_at), add_at_tick);
+
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    update(&mut server_app, &mut client_app);
+    update(&mut server_app, &mut client_app);
+    assert!(!server_app.world_mut().syscall(client_id, has_revealed));
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scheduled_changes_dropped_on_disconnect()
+{
+    let (mut server_app, mut client_app) = setup();
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    update(&mut server_app, &mut client_app);
+
+    let remove_at = current_tick(&server_app) + 2;
+    server_app.world_mut().syscall((client_id, Duration::from_millis(150)), add_for);
+    server_app.world_mut().syscall((client_id, remove_at), remove_at_tick);
+    server_app.update();
+    assert!(server_app.world_mut().syscall(client_id, has_revealed));
+
+    // Pending changes don't re-add the client after it disconnects.
+    server_app.disconnect_client(&mut client_app);
+    for _ in 0..3 { server_app.update(); }
+    assert!(server_app.world_mut().syscall(client_id, get_attributes).is_none());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
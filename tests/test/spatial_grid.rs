@@ -0,0 +1,196 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn setup(dimensions: GridDimensions) -> (App, App)
+{
+    common::setup_with(VisibilitySettings::default(), SpatialGridPlugin(SpatialGrid{ cell_size: 10.0, view_radius: 1, dimensions }))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn spawn_at(server_app: &mut App, position: Vec3) -> Entity
+{
+    server_app.world_mut().spawn((Replicated, ComponentA, Transform::from_translation(position), SpatialGridEntity)).id()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn move_to(server_app: &mut App, entity: Entity, position: Vec3)
+{
+    server_app.world_mut().get_mut::<Transform>(entity).unwrap().translation = position;
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn viewer_sees_nearby_cells()
+{
+    let (mut server_app, mut client_app) = setup(GridDimensions::Xy);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Transform::from_xyz(5.0, 5.0, 0.0), GridViewer(client_id)));
+    spawn_at(&mut server_app, Vec3::new(1.0, 1.0, 0.0));
+    spawn_at(&mut server_app, Vec3::new(-9.0, 19.0, 0.0));
+    spawn_at(&mut server_app, Vec3::new(25.0, 5.0, 0.0));
+    spawn_at(&mut server_app, Vec3::new(-11.0, 5.0, 0.0));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // Viewers on other clients don't affect this client.
+    server_app.world_mut().spawn((Transform::from_xyz(25.0, 5.0, 0.0), GridViewer(client_id + 1)));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // Multiple viewers on one client combine their views.
+    let viewer = server_app.world_mut().spawn((Transform::from_xyz(25.0, 5.0, 0.0), GridViewer(client_id))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    server_app.world_mut().entity_mut(viewer).remove::<GridViewer>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn updates_on_cell_boundaries()
+{
+    let (mut server_app, mut client_app) = setup(GridDimensions::Xy);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    let viewer = server_app.world_mut().spawn((Transform::from_xyz(5.0, 5.0, 0.0), GridViewer(client_id))).id();
+    let entity = spawn_at(&mut server_app, Vec3::new(15.0, 5.0, 0.0));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    assert_eq!(server_app.world().get::<GridCell>(entity), Some(&GridCell(IVec3::new(1, 0, 0))));
+
+    // Moving within a cell does not touch the condition.
+    let last_changed = server_app.world().entity(entity).get_ref::<VisibilityCondition>().unwrap().last_changed();
+    move_to(&mut server_app, entity, Vec3::new(19.0, 1.0, 0.0));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().entity(entity).get_ref::<VisibilityCondition>().unwrap().last_changed(), last_changed);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Crossing out of view.
+    move_to(&mut server_app, entity, Vec3::new(20.0, 1.0, 0.0));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+    assert_eq!(server_app.world().get::<GridCell>(entity), Some(&GridCell(IVec3::new(2, 0, 0))));
+
+    // The viewer follows.
+    move_to(&mut server_app, viewer, Vec3::new(11.0, 5.0, 0.0));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Removing the grid component removes the condition.
+    server_app.world_mut().entity_mut(entity).remove::<SpatialGridEntity>();
+    update(&mut server_app, &mut client_app);
+    assert!(server_app.world().get::<VisibilityCondition>(entity).is_none());
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn entities_added_when_replicated()
+{
+    let (mut server_app, mut client_app) = setup(GridDimensions::Xy);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Transform::from_xyz(5.0, 5.0, 0.0), GridViewer(client_id)));
+    let entity = server_app.world_mut().spawn((ComponentA, Transform::from_xyz(1.0, 1.0, 0.0), SpatialGridEntity)).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<GridCell>(entity), None);
+
+    // Entities get a cell when they start replicating, even if they haven't moved.
+    server_app.world_mut().entity_mut(entity).insert(Replicated);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<GridCell>(entity), Some(&GridCell(IVec3::new(0, 0, 0))));
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn grid_dimensions()
+{
+    // 3D grids use all axes.
+    let (mut server_app, mut client_app) = setup(GridDimensions::Xyz);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Transform::from_xyz(5.0, 5.0, 5.0), GridViewer(client_id)));
+    spawn_at(&mut server_app, Vec3::new(5.0, 5.0, 15.0));
+    spawn_at(&mut server_app, Vec3::new(5.0, 5.0, 25.0));
+    spawn_at(&mut server_app, Vec3::new(-5.0, 15.0, -5.0));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // 2D grids ignore the unused axis.
+    let (mut server_app, mut client_app) = setup(GridDimensions::Xz);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Transform::from_xyz(5.0, 5.0, 5.0), GridViewer(client_id)));
+    spawn_at(&mut server_app, Vec3::new(5.0, 500.0, 15.0));
+    spawn_at(&mut server_app, Vec3::new(5.0, 5.0, 25.0));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn grid_changes_resync()
+{
+    let (mut server_app, mut client_app) = setup(GridDimensions::Xy);
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Transform::from_xyz(5.0, 5.0, 0.0), GridViewer(client_id)));
+    let entity = spawn_at(&mut server_app, Vec3::new(25.0, 5.0, 0.0));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // A larger view radius reveals the entity.
+    server_app.world_mut().resource_mut::<SpatialGrid>().view_radius = 2;
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Smaller cells move the entity out of view.
+    server_app.world_mut().resource_mut::<SpatialGrid>().cell_size = 5.0;
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<GridCell>(entity), Some(&GridCell(IVec3::new(5, 1, 0))));
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // Invalid cell sizes are ignored.
+    server_app.world_mut().resource_mut::<SpatialGrid>().cell_size = 0.0;
+    move_to(&mut server_app, entity, Vec3::new(5.0, 5.0, 0.0));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(server_app.world().get::<GridCell>(entity), Some(&GridCell(IVec3::new(5, 1, 0))));
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+// cell sizes must be positive
+#[should_panic]
+#[test]
+fn zero_cell_size_panics()
+{
+    App::new().add_plugins((
+            MinimalPlugins,
+            RepliconPlugins.set(bevy_replicon::prelude::ServerPlugin {
+                visibility_policy: VisibilityPolicy::Whitelist,
+                ..Default::default()
+            }),
+        ))
+        .add_plugins(VisibilityAttributesPlugin{ server_id: None, reconnect_policy: ReconnectPolicy::Reset })
+        .add_plugins(SpatialGridPlugin(SpatialGrid{ cell_size: 0.0, view_radius: 1, dimensions: GridDimensions::Xy }))
+        .finish();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
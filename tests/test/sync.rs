@@ -0,0 +1,159 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+use std::collections::HashSet;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct A;
+
+#[derive(VisibilityAttribute)]
+struct InCell(u32);
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn sync_cells(In((client_id, cells)): In<(u64, Vec<u32>)>, mut attributes: ClientAttributes)
+{
+    attributes.sync_type(client_id, cells.into_iter().map(InCell));
+}
+
+fn sync_all(In((client_id, ids)): In<(u64, Vec<VisibilityAttributeId>)>, mut attributes: ClientAttributes)
+{
+    attributes.sync(client_id, ids);
+}
+
+fn grant_cell(In((client_id, cell)): In<(u64, u32)>, mut attributes: ClientAttributes)
+{
+    attributes.grant(client_id, InCell(cell), AttributeSource::Id(0));
+}
+
+fn revoke_cell(In((client_id, cell)): In<(u64, u32)>, mut attributes: ClientAttributes)
+{
+    attributes.revoke(client_id, InCell(cell), AttributeSource::Id(0));
+}
+
+fn get_attributes(In(client_id): In<u64>, attributes: ClientAttributes) -> HashSet<VisibilityAttributeId>
+{
+    attributes.get(client_id).cloned().unwrap_or_default()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn sync_type_replaces_scoped_attributes()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCell(1))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCell(2))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(and(InCell(2), InCell(3)))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(A)));
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+
+    server_app.world_mut().syscall((client_id, vec![1, 2]), sync_cells);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    server_app.world_mut().syscall((client_id, vec![2, 3]), sync_cells);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+    assert_eq!(
+        server_app.world_mut().syscall(client_id, get_attributes),
+        HashSet::from([
+            Global.attribute_id(),
+            Client(client_id).attribute_id(),
+            A.attribute_id(),
+            InCell(2).attribute_id(),
+            InCell(3).attribute_id(),
+        ])
+    );
+
+    server_app.world_mut().syscall((client_id, vec![]), sync_cells);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn sync_type_keeps_granted_attributes()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCell(1))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCell(2))));
+    server_app.world_mut().syscall((client_id, 1), grant_cell);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    // Synced attributes don't remove granted attributes.
+    server_app.world_mut().syscall((client_id, vec![2]), sync_cells);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // Revoking the grant removes the attribute.
+    server_app.world_mut().syscall((client_id, 1), revoke_cell);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    assert_eq!(
+        server_app.world_mut().syscall(client_id, get_attributes),
+        HashSet::from([
+            Global.attribute_id(),
+            Client(client_id).attribute_id(),
+            InCell(2).attribute_id(),
+        ])
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn sync_replaces_all_attributes()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(InCell(1))));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(A)));
+    server_app.world_mut().spawn((Replicated, ComponentA, vis!(Global)));
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // Attributes of all types are replaced, including builtin attributes.
+    server_app.world_mut().syscall((client_id, vec![InCell(1).attribute_id(), Client(client_id).attribute_id()]), sync_all);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+    assert_eq!(
+        server_app.world_mut().syscall(client_id, get_attributes),
+        HashSet::from([
+            Client(client_id).attribute_id(),
+            InCell(1).attribute_id(),
+        ])
+    );
+
+    // Granted attributes are kept.
+    server_app.world_mut().syscall((client_id, 1), grant_cell);
+    server_app.world_mut().syscall((client_id, vec![A.attribute_id()]), sync_all);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+    assert_eq!(
+        server_app.world_mut().syscall(client_id, get_attributes),
+        HashSet::from([
+            A.attribute_id(),
+            InCell(1).attribute_id(),
+        ])
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,195 @@
+//local shortcuts
+use crate::*;
+use bevy_replicon_attributes::*;
+use bevy_replicon_attributes::not;
+
+//third-party shortcuts
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+use bevy_replicon::prelude::*;
+
+//standard shortcuts
+
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct A;
+
+#[derive(VisibilityAttribute, Default, PartialEq)]
+struct B;
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+fn condition(app: &App, entity: Entity) -> Option<&VisibilityCondition>
+{
+    app.world().get::<VisibilityCondition>(entity)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn force_visible(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.force_visible(client_id, entity);
+}
+
+fn force_hidden(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.force_hidden(client_id, entity);
+}
+
+fn clear_override(In((client_id, entity)): In<(u64, Entity)>, mut attributes: ClientAttributes)
+{
+    attributes.clear_override(client_id, entity);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn visible_with_chain()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    let anchor = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    let effect = server_app.world_mut().spawn((Replicated, ComponentA, VisibleWith(anchor))).id();
+    let nameplate = server_app.world_mut().spawn((Replicated, ComponentA, VisibleWith(effect))).id();
+    server_app.world_mut().spawn((Replicated, ComponentA, InheritVisibility::and(B), ChildOf(nameplate)));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(condition(&server_app, effect), None);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+
+    server_app.world_mut().syscall((client_id, B), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 4);
+
+    // anchor changes propagate down the chain
+    server_app.world_mut().entity_mut(anchor).insert(vis!(not(B)));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // re-anchor
+    let other = server_app.world_mut().spawn((Replicated, ComponentA, vis!(or(A, Global)))).id();
+    server_app.world_mut().entity_mut(nameplate).insert(VisibleWith(other));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn visible_with_anchor_despawn()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    let anchor = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    let effect = server_app.world_mut().spawn((Replicated, ComponentA, VisibleWith(anchor), vis!(B))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // the dependent returns to its own condition
+    server_app.world_mut().despawn(anchor);
+    update(&mut server_app, &mut client_app);
+    assert!(server_app.world().get::<VisibleWith>(effect).is_none());
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    server_app.world_mut().syscall((client_id, B), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn visible_with_cycle()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    common::connect(&mut server_app, &mut client_app, 1);
+    let a = server_app.world_mut().spawn((Replicated, ComponentA, vis!(Global))).id();
+    let b = server_app.world_mut().spawn((Replicated, ComponentA, VisibleWith(a))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().entity_mut(a).insert(VisibleWith(b));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // breaking the cycle restores visibility
+    server_app.world_mut().entity_mut(a).remove::<VisibleWith>();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn visible_with_follows_anchor_output()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    let anchor = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A), VisibilityLinger::Ticks(2))).id();
+    let effect = server_app.world_mut().spawn((Replicated, ComponentA, VisibleWith(anchor))).id();
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // the dependent lingers with the anchor
+    server_app.world_mut().syscall((client_id, A), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+    update(&mut server_app, &mut client_app);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // the anchor's overrides apply to the dependent
+    server_app.world_mut().syscall((client_id, anchor), force_visible);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    // the dependent's own overrides take precedence
+    server_app.world_mut().syscall((client_id, effect), force_hidden);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 1);
+
+    server_app.world_mut().syscall((client_id, effect), clear_override);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn visible_with_replication()
+{
+    let (mut server_app, mut client_app) = setup(VisibilitySettings::default());
+    let client_id = common::connect(&mut server_app, &mut client_app, 1);
+
+    let anchor = server_app.world_mut().spawn((Replicated, ComponentA, vis!(A))).id();
+    server_app.world_mut().spawn((Replicated, ComponentA, VisibleWith(anchor)));
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // anchor and dependent become visible together when client attributes change
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().syscall((client_id, A), remove_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+
+    // anchor despawn hides the dependent
+    server_app.world_mut().syscall((client_id, A), add_attribute);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3 + 2);
+
+    server_app.world_mut().despawn(anchor);
+    update(&mut server_app, &mut client_app);
+    assert_eq!(client_app.world().entities().len(), 3);
+}
+
+//-------------------------------------------------------------------------------------------------------------------